@@ -0,0 +1,139 @@
+use oort_api::prelude::*;
+
+use crate::class_info::{class_abilities, AbilitySpec};
+
+//Incoming fire risk (see Evasion) above which the shield is raised
+const SHIELD_RISK_THRESHOLD: f64 = 0.5;
+//Deploy a decoy when a missile is this close to impact
+const DECOY_TIME_TO_IMPACT: f64 = 1.5; //s
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AbilityState {
+    Unavailable, //This class doesn't have it
+    Ready,
+    Active { remaining: u32 },
+    Cooldown { remaining: u32 },
+}
+
+//What the rest of the AI wants this tick. The manager turns it into ability use.
+#[derive(Default)]
+pub struct AbilityRequest {
+    pub closing: bool,  //Want to close range fast, and pointing the right way to do it
+    pub escaping: bool, //Want to get away
+    pub incoming_fire_risk: f64,
+    pub missile_time_to_impact: Option<f64>, //Most imminent missile homing on us
+}
+
+struct AbilitySlot {
+    spec: AbilitySpec,
+    activated_tick: Option<u32>,
+    ended_early: bool,
+}
+
+//Knows which abilities our class has and their timings, decides when to use them from an
+//AbilityRequest and tracks whether each one is ready, active or cooling down.
+#[derive(Default)]
+pub struct AbilityManager {
+    slots: Vec<AbilitySlot>,
+}
+
+impl AbilityManager {
+    pub fn new(class: Class) -> AbilityManager {
+        return AbilityManager {
+            slots: class_abilities(class)
+                .into_iter()
+                .map(|spec| AbilitySlot {
+                    spec: spec,
+                    activated_tick: None,
+                    ended_early: false,
+                })
+                .collect(),
+        };
+    }
+
+    pub fn state(&self, ability: Ability) -> AbilityState {
+        let slot = match self.slot(ability) {
+            Some(slot) => slot,
+            None => return AbilityState::Unavailable,
+        };
+
+        let activated_tick = match slot.activated_tick {
+            Some(tick) => tick,
+            None => return AbilityState::Ready,
+        };
+
+        let elapsed = current_tick() - activated_tick;
+        if elapsed < slot.spec.duration && !slot.ended_early {
+            return AbilityState::Active {
+                remaining: slot.spec.duration - elapsed,
+            };
+        }
+        if elapsed < slot.spec.cooldown {
+            return AbilityState::Cooldown {
+                remaining: slot.spec.cooldown - elapsed,
+            };
+        }
+        return AbilityState::Ready;
+    }
+
+    pub fn is_active(&self, ability: Ability) -> bool {
+        return matches!(self.state(ability), AbilityState::Active { .. });
+    }
+
+    pub fn is_ready(&self, ability: Ability) -> bool {
+        return self.state(ability) == AbilityState::Ready;
+    }
+
+    //Activates the ability if it's ready. Returns whether it was activated.
+    pub fn activate(&mut self, ability: Ability) -> bool {
+        if !self.is_ready(ability) {
+            return false;
+        }
+        activate_ability(ability);
+        if let Some(slot) = self.slot_mut(ability) {
+            slot.activated_tick = Some(current_tick());
+            slot.ended_early = false;
+        }
+        return true;
+    }
+
+    //Ends an active ability early. The cooldown still runs from activation.
+    pub fn deactivate(&mut self, ability: Ability) {
+        if self.is_active(ability) {
+            deactivate_ability(ability);
+            if let Some(slot) = self.slot_mut(ability) {
+                slot.ended_early = true;
+            }
+        }
+    }
+
+    //Call every tick
+    pub fn tick(&mut self, request: &AbilityRequest) {
+        if request.closing || request.escaping {
+            self.activate(Ability::Boost);
+        } else {
+            self.deactivate(Ability::Boost);
+        }
+
+        if request.incoming_fire_risk >= SHIELD_RISK_THRESHOLD {
+            self.activate(Ability::Shield);
+        }
+
+        if let Some(time_to_impact) = request.missile_time_to_impact {
+            if time_to_impact <= DECOY_TIME_TO_IMPACT {
+                self.activate(Ability::Decoy);
+            }
+        }
+    }
+
+    fn slot(&self, ability: Ability) -> Option<&AbilitySlot> {
+        return self.slots.iter().find(|slot| slot.spec.ability == ability);
+    }
+
+    fn slot_mut(&mut self, ability: Ability) -> Option<&mut AbilitySlot> {
+        return self
+            .slots
+            .iter_mut()
+            .find(|slot| slot.spec.ability == ability);
+    }
+}
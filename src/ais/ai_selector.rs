@@ -3,7 +3,42 @@ use oort_api::prelude::*;
 use crate::ais::ai::AI;
 use crate::ais::deflection::Deflection;
 
+use super::deflection::FallbackTarget;
+use super::gunnery::Gunnery;
+use super::homing::Homing;
 use super::kinematics_test::KinematicsTest;
+use super::radio_target::RadioTarget;
+use super::squadron::Squadron;
+use super::waypoint::Waypoint;
+
+//Scenarios that need a dedicated AI, everything else is chosen by class. Together with
+//get_class_ai (frigate and cruiser tutorials) this covers every tutorial.
+pub fn get_scenario_ai(scenario: &str, class: Class) -> Option<Box<dyn AI>> {
+    //Missiles and torpedoes fly the same whoever launched them
+    if class == Class::Missile || class == Class::Torpedo {
+        return None;
+    }
+
+    return match scenario {
+        "tutorial_guns" => Some(Box::new(Gunnery::new())),
+        "tutorial_acceleration" => Some(Box::new(Waypoint::new())),
+        "tutorial_acceleration2" => Some(Box::new(Waypoint::new())),
+        "tutorial_rotation" => Some(Box::new(Gunnery::new())),
+        //Fighters otherwise get KinematicsTest, see get_class_ai
+        "tutorial_lead" => Some(Box::new(Deflection::new())),
+        "tutorial_deflection" => Some(Box::new(Deflection::new())),
+        "tutorial_radar" | "tutorial_search" => {
+            //No target(), radar is the only way to find the enemy
+            let mut ai = Deflection::new();
+            ai.set_fallback_target(FallbackTarget::None);
+            Some(Box::new(ai))
+        }
+        "tutorial_radio" => Some(Box::new(RadioTarget::new())),
+        "tutorial_missiles" => Some(Box::new(Deflection::new())),
+        "tutorial_squadron" => Some(Box::new(Squadron::new())),
+        _ => None,
+    };
+}
 
 pub fn get_class_ai(class: Class) -> Box<dyn AI> {
     match class {
@@ -12,30 +47,9 @@ pub fn get_class_ai(class: Class) -> Box<dyn AI> {
         Class::Cruiser => Box::new(Deflection::new()),
         Class::Asteroid => Box::new(Deflection::new()),
         Class::Target => Box::new(Deflection::new()),
-        Class::Missile => Box::new(Deflection::new()),
-        Class::Torpedo => Box::new(Deflection::new()),
+        Class::Missile => Box::new(Homing::new()),
+        Class::Torpedo => Box::new(Homing::new()),
         Class::Unknown => Box::new(Deflection::new()),
         _ => Box::new(Deflection::new()),
     }
 }
-
-//future possibility: match ai against class AND scenario
-
-// pub fn get_ai_for_scenario() -> Box<dyn AI> {
-//     return match scenario_name() {
-//         "tutorial_guns" => Box::new(Deflection::new()),
-//         "tutorial_acceleration" => Box::new(Deflection::new()),
-//         "tutorial_acceleration2" => Box::new(Deflection::new()),
-//         "tutorial_rotation" => Box::new(Deflection::new()),
-//         "tutorial_lead" => Box::new(Deflection::new()),
-//         "tutorial_deflection" => Box::new(Deflection::new()),
-//         "tutorial_radar" => Box::new(Deflection::new()),
-//         "tutorial_search" => Box::new(Deflection::new()),
-//         "tutorial_radio" => Box::new(Deflection::new()),
-//         "tutorial_missiles" => Box::new(Deflection::new()),
-//         "tutorial_squadron" => Box::new(Deflection::new()),
-//         "tutorial_frigate" => Box::new(Deflection::new()),
-//         "tutorial_cruiser" => Box::new(Deflection::new()),
-//         &_ => Box::new(Deflection::new()),
-//     };
-// }
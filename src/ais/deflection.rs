@@ -1,27 +1,87 @@
 use std::{any::type_name, collections::VecDeque};
 
 use super::ai::AI;
+use crate::abilities::*;
+use crate::class_info::{class_info, class_launchers};
 use crate::constants::*;
+use crate::debug_draw::{self, DebugCategory};
+use crate::evasion::*;
+use crate::fire_control::FireControl;
 use crate::graphing::graphing::*;
+use crate::graphing::scatter::*;
+use crate::jinking::Jinking;
 use crate::kinematics::*;
+use crate::lead_correction::LeadCorrection;
+use crate::maneuver_envelope::reachable_envelope;
+use crate::point_defense::PointDefense;
+use crate::radar::scheduler::RadarScheduler;
+use crate::shot_ledger::*;
+use crate::spread_fire::{aim_probability, SpreadFire};
+use crate::threat_assessment::{prioritize, TargetScore};
+use crate::tracking::{Track, TrackTable};
+use crate::trajectory::*;
 use oort_api::prelude::*;
 
 const GRAPH_TIMESPAN: f64 = 3.0;
 const GRAPH_WIDTH: f64 = 2000.0;
 const GRAPH_HEIGHT: f64 = 400.0;
 const GRAPH_X: f64 = -GRAPH_WIDTH / 2.0;
+const SCATTER_SIZE: f64 = 900.0;
+const SCATTER_X: f64 = GRAPH_X + GRAPH_WIDTH + 200.0;
+const TRAJECTORY_TICKS: i32 = 120;
+const TRAJECTORY_STEP: i32 = 10;
+const ENVELOPE_DIRECTIONS: usize = 16;
+const CONTACT_MAX_AGE: u32 = 120;
+//Don't waste missiles on targets further than this
+const LAUNCH_RANGE: f64 = 5000.0; //m
+
+//Incoming fire risk (see Evasion) above which we boost clear when we can't shoot back
+const ESCAPE_RISK: f64 = 0.7;
+//With nothing to shoot, circle at this rate so the guns sweep round while radar searches
+const PATROL_TURN_RATE: f64 = TAU / 8.0; //rad/s
+const PATROL_THRUST: f64 = 0.5; //Fraction of max forward acceleration
+
+//What to shoot when radar hasn't found anything
+#[derive(Clone, Copy, PartialEq)]
+pub enum FallbackTarget {
+    Scenario, //target() and target_velocity(), for tutorials that provide them
+    External { position: Vec2, velocity: Vec2 }, //Fed in from elsewhere, eg the radio
+    None,     //Hold fire and patrol until radar finds something
+}
+
+impl Default for FallbackTarget {
+    fn default() -> Self {
+        return FallbackTarget::Scenario;
+    }
+}
 
 #[derive(Default)]
 pub struct Deflection {
-    target_last_velocity: Vec2,
-    target_last_accel: Vec2,
+    target_track: Track,   //Estimated state of whatever we're shooting at this tick
+    fallback_track: Track, //Tracks the fallback target, see FallbackTarget
+    target_id: Option<u32>,
+    ally_targets: Vec<Vec2>, //Targets allies are engaging, world positions
+    fallback: FallbackTarget,
+    launchers: Vec<usize>, //Missile and torpedo weapon indices
+    own_track: Track,
     target_last_heading: f64,
-    bullets_fired: i32,
+    fire_control: FireControl,
+    shot_ledger: ShotLedger,
+    lead_correction: LeadCorrection,
+    spread_fire: SpreadFire,
+    contacts: TrackTable,
+    radar: RadarScheduler,
+    evasion: Evasion,
+    jinking: Jinking,
+    abilities: AbilityManager,
+    point_defense: PointDefense,
     graph1: Graph,
     graph2: Graph,
     graph3: Graph,
     graph4: Graph,
     graph5: Graph,
+    phase_graph: ScatterGraph,
+    aim_error_graph: ScatterGraph,
 }
 
 impl Deflection {
@@ -37,6 +97,8 @@ impl Deflection {
                 auto_shrink: false,
                 timespan: GRAPH_TIMESPAN,
                 color: 0xff0000,
+                show_stats: true,
+                show_stat_bands: true,
                 ..Default::default()
             },
             graph2: Graph {
@@ -45,6 +107,7 @@ impl Deflection {
                 size: vec2(GRAPH_WIDTH, GRAPH_HEIGHT),
                 timespan: GRAPH_TIMESPAN,
                 color: 0x00ffff,
+                decimation: Decimation::Budget { max_lines: 60 },
                 ..Default::default()
             },
             graph3: Graph {
@@ -53,6 +116,7 @@ impl Deflection {
                 size: vec2(GRAPH_WIDTH, GRAPH_HEIGHT),
                 timespan: GRAPH_TIMESPAN,
                 color: 0x00ff00,
+                decimation: Decimation::Budget { max_lines: 60 },
                 ..Default::default()
             },
             graph4: Graph {
@@ -61,6 +125,7 @@ impl Deflection {
                 size: vec2(GRAPH_WIDTH, GRAPH_HEIGHT),
                 timespan: GRAPH_TIMESPAN,
                 color: 0xff8800,
+                decimation: Decimation::Budget { max_lines: 60 },
                 ..Default::default()
             },
             graph5: Graph {
@@ -71,10 +136,74 @@ impl Deflection {
                 color: 0xff00ff,
                 ..Default::default()
             },
+            phase_graph: ScatterGraph {
+                title: String::from("phase"),
+                x_label: String::from("ang delta"),
+                y_label: String::from("ang vel delta"),
+                position: vec2(SCATTER_X, 0.0),
+                size: vec2(SCATTER_SIZE, SCATTER_SIZE),
+                timespan: GRAPH_TIMESPAN,
+                color: 0xffff00,
+                ..Default::default()
+            },
+            aim_error_graph: ScatterGraph {
+                title: String::from("aim error"),
+                position: vec2(SCATTER_X, -1000.0),
+                size: vec2(SCATTER_SIZE, SCATTER_SIZE),
+                timespan: GRAPH_TIMESPAN,
+                style: ScatterStyle::Points,
+                color: 0x00ff88,
+                ..Default::default()
+            },
+            jinking: Jinking::new(),
+            abilities: AbilityManager::new(class()),
+            point_defense: PointDefense::new(class()),
+            launchers: class_launchers(class()),
             ..Default::default()
         };
     }
 
+    //Targets allies are already engaging, so we prefer others. Applies from the next tick.
+    pub fn set_ally_targets(&mut self, ally_targets: Vec<Vec2>) {
+        self.ally_targets = ally_targets;
+    }
+
+    pub fn set_fallback_target(&mut self, fallback: FallbackTarget) {
+        self.fallback = fallback;
+    }
+
+    //The contact we're currently engaging, None while shooting at the scenario's target()
+    pub fn target(&self) -> Option<&Track> {
+        return self.target_id.and_then(|id| self.contacts.get(id));
+    }
+
+    //Flies a circle with the nose sweeping round until radar finds something, still dodging and
+    //defending ourselves
+    fn patrol(&mut self, priorities: &[TargetScore]) {
+        let max_angular_accel = max_angular_acceleration();
+        torque(
+            ((PATROL_TURN_RATE - angular_velocity()) / TICK_LENGTH)
+                .clamp(-max_angular_accel, max_angular_accel),
+        );
+        let dodge = self.evasion.tick(&self.contacts, 0.0);
+        accelerate(vec2(max_forward_acceleration() * PATROL_THRUST, 0.0).rotate(heading()) + dodge);
+
+        self.point_defense.tick(&self.contacts, priorities);
+        self.abilities.tick(&AbilityRequest {
+            closing: false,
+            escaping: self.evasion.risk >= ESCAPE_RISK,
+            incoming_fire_risk: self.evasion.risk,
+            missile_time_to_impact: most_imminent_missile(&self.contacts)
+                .map(|threat| threat.time_to_impact),
+        });
+
+        self.lead_correction.tick();
+        for result in self.shot_ledger.tick(&self.contacts, &self.fallback_track) {
+            self.lead_correction
+                .learn(&result, self.shot_ledger.pending.len());
+        }
+    }
+
     //Turns ship to track a moving target. Automatically calculates target velocity.
     //Self frame of reference
     fn track(&mut self, target_heading: f64) {
@@ -114,10 +243,10 @@ impl Deflection {
 
         let opt_vel = get_optimal_arrival_velocity_v3(angle_delta, ttt, max_angular_acceleration());
 
-        debug!("accel: {}", accel);
-        debug!("max accel: {}", max_accel);
+        debug_log!("accel: {}", accel);
+        debug_log!("max accel: {}", max_accel);
 
-        self.graph1.add(f64::NAN);
+        self.graph1.add(angle_delta);
         self.graph1.tick();
 
         self.graph2.add(accel);
@@ -132,27 +261,96 @@ impl Deflection {
         // self.graph5.add();
         // self.graph5.tick();
 
+        self.phase_graph
+            .add(angle_delta, target_angular_velocity - angular_velocity());
+        self.phase_graph.tick();
+
         self.target_last_heading = target_heading;
     }
 }
 
+//Position and velocity of the fallback target this tick, None to hold fire. scenario gives
+//target() and target_velocity(), it's only called for FallbackTarget::Scenario.
+pub fn fallback_state(
+    fallback: FallbackTarget,
+    scenario: impl FnOnce() -> (Vec2, Vec2),
+) -> Option<(Vec2, Vec2)> {
+    return match fallback {
+        FallbackTarget::Scenario => Some(scenario()),
+        FallbackTarget::External { position, velocity } => Some((position, velocity)),
+        FallbackTarget::None => None,
+    };
+}
+
 // #[derive(New)]
 impl AI for Deflection {
     fn tick(&mut self) {
-        debug!("tick: {}", current_tick());
-        let target_delta = target() - position();
-        let target_velocity_delta = target_velocity() - velocity();
-        let target_accel = (target_velocity() - self.target_last_velocity) / TICK_LENGTH;
-        let target_jerk = (target_accel - self.target_last_accel) / TICK_LENGTH;
-        let bullet_intercept = predict_intercept(
+        debug_log!("tick: {}", current_tick());
+        self.own_track.update(position(), velocity());
+        self.radar.tick(&mut self.contacts);
+        self.contacts.prune(CONTACT_MAX_AGE);
+
+        //Shoot the highest priority contact, or the fallback target until radar finds something
+        let priorities = prioritize(&self.contacts, &self.fire_control, &self.ally_targets);
+        let top = priorities
+            .first()
+            .and_then(|score| self.contacts.get(score.id));
+        match top {
+            //The radar track already has the estimated state, feeding it extrapolated positions
+            //between looks would only add fake acceleration
+            Some(track) => self.target_track = *track,
+            None => match fallback_state(self.fallback, || (target(), target_velocity())) {
+                Some((position, velocity)) => {
+                    self.fallback_track.update(position, velocity);
+                    self.target_track = self.fallback_track;
+                }
+                None => {
+                    self.patrol(&priorities);
+                    return;
+                }
+            },
+        }
+        self.target_id = top.map(|track| track.id);
+        //Hold on to the target if it drops out of the scan
+        self.radar.set_priority(self.target_id);
+
+        let target_position = self.target_track.current_position();
+        let target_vel = self.target_track.velocity;
+        let target_delta = target_position - position();
+        let target_velocity_delta = target_vel - velocity();
+        let target_accel = self.target_track.accel;
+        let target_jerk = self.target_track.jerk;
+        let predicted_intercept = predict_intercept(
             target_delta,
             target_velocity_delta,
             target_accel,
             target_jerk,
             BULLET_SPEED,
         );
+        self.lead_correction.tick();
+        let bullet_intercept = self.lead_correction.correct(
+            predicted_intercept,
+            &self.target_track,
+            predicted_intercept.length() / BULLET_SPEED,
+        );
+
+        //Against a target maneuvering beyond our model, aim at a spread of possible intercepts
+        self.spread_fire.tick();
+        let mut aim_intercept = bullet_intercept;
+        let mut spread_shot = None;
+        if self.spread_fire.is_needed(&self.target_track) {
+            let candidates = self.spread_fire.candidates(
+                target_delta,
+                target_velocity_delta,
+                bullet_intercept,
+                &self.target_track,
+            );
+            let index = self.spread_fire.select(&candidates);
+            aim_intercept = candidates[index].intercept;
+            spread_shot = Some((index, candidates));
+        }
 
-        let bullet_intercept_angle = bullet_intercept.angle();
+        let bullet_intercept_angle = aim_intercept.angle();
         let delta_angle = angle_diff(heading(), bullet_intercept_angle);
 
         let ship_intercept = predict_intercept(
@@ -163,45 +361,192 @@ impl AI for Deflection {
             350.0,
         );
 
-        accelerate(ship_intercept.normalize() * max_forward_acceleration());
+        let jink = self.jinking.tick(target_delta.length());
+        let dodge = self.evasion.tick(&self.contacts, jink);
+        accelerate(ship_intercept.normalize() * max_forward_acceleration() + dodge);
         self.track(bullet_intercept_angle);
 
-        // if angle_diff(heading(), ship_intercept.angle()).abs() <= TAU / 5.0 && current_tick() > 2 {
-        if angle_diff(heading(), ship_intercept.angle()).abs() <= TAU / 5.0 && current_tick() > 2 {
-            activate_ability(Ability::Boost);
-        } else {
-            deactivate_ability(Ability::Boost);
+        self.point_defense.tick(&self.contacts, &priorities);
+
+        let mut solution =
+            self.fire_control
+                .evaluate(delta_angle, aim_intercept, &self.target_track);
+        let mut should_fire = self.fire_control.should_fire(&solution);
+        if let Some((index, candidates)) = &spread_shot {
+            //The single point estimate is hopeless against this target. Fire when we'd hit the
+            //candidate we're aiming at, and count the maneuvers the shot covers for the stats.
+            let aimed = aim_probability(&candidates[*index], heading(), solution.target_radius);
+            should_fire = aimed >= self.fire_control.threshold;
+            solution.hit_probability =
+                self.spread_fire
+                    .hit_probability(candidates, heading(), solution.target_radius);
         }
 
-        let fire_angle_threshold = TAU * 1.65 / bullet_intercept.length();
-        if delta_angle.abs() <= fire_angle_threshold {
+        self.abilities.tick(&AbilityRequest {
+            closing: angle_diff(heading(), ship_intercept.angle()).abs() <= TAU / 5.0
+                && current_tick() > 2,
+            //Taking heavy fire with nothing to shoot back at
+            escaping: self.evasion.risk >= ESCAPE_RISK && !should_fire,
+            incoming_fire_risk: self.evasion.risk,
+            missile_time_to_impact: most_imminent_missile(&self.contacts)
+                .map(|threat| threat.time_to_impact),
+        });
+
+        //Cruisers share their main gun with point defense, incoming missiles take precedence
+        if reload_ticks(0) == 0 && should_fire && !self.point_defense.is_engaging(0) {
             fire(0);
-            self.bullets_fired += 1;
+            self.fire_control.record_shot(&solution);
+            if let Some((index, _)) = spread_shot {
+                self.spread_fire.record_shot(index);
+            }
+            self.shot_ledger.record(Shot {
+                target_id: self.target_id,
+                fire_tick: current_tick(),
+                impact_tick: current_tick()
+                    + (solution.time_of_flight / TICK_LENGTH).round() as u32,
+                bullet_origin: position(),
+                bullet_velocity: velocity() + vec2(BULLET_SPEED, 0.0).rotate(heading()),
+                predicted_target_pos: position()
+                    + velocity() * solution.time_of_flight
+                    + bullet_intercept,
+            });
         }
-        debug!("fired: {}", self.bullets_fired);
+        //Missiles find their own way, just launch them while the target is in range
+        if target_delta.length() <= LAUNCH_RANGE {
+            for &index in &self.launchers {
+                if reload_ticks(index) == 0 {
+                    fire(index);
+                }
+            }
+        }
+
+        for result in self.shot_ledger.tick(&self.contacts, &self.fallback_track) {
+            self.lead_correction
+                .learn(&result, self.shot_ledger.pending.len());
+        }
+        debug_log!("fired: {}", self.fire_control.shots);
+        debug_log!("hit probability: {}", solution.hit_probability);
+        debug_log!("spread: {}", spread_shot.is_some());
+        debug_log!(
+            "dodge: {} risk: {}",
+            self.evasion.lateral,
+            self.evasion.risk
+        );
+        debug_log!(
+            "expected hits/bullet: {}",
+            self.fire_control.expected_hits_per_bullet()
+        );
+        debug_log!(
+            "accuracy: {} ({}/{}) mean miss: {}",
+            self.shot_ledger.accuracy(),
+            self.shot_ledger.hits,
+            self.shot_ledger.resolved,
+            self.shot_ledger.mean_miss_distance()
+        );
+        debug_log!(
+            "bias along: {} cross: {}",
+            self.shot_ledger.along_track_bias,
+            self.shot_ledger.cross_track_bias
+        );
+        debug_log!(
+            "lead correction along: {} cross: {}",
+            self.lead_correction.along_track_accel,
+            self.lead_correction.cross_track_accel
+        );
+
+        //Angle either side of the intercept within which a bullet passes through the target
+        let fire_angle_threshold = (solution.target_radius / aim_intercept.length()).atan();
 
-        draw_line(
+        //Where the bullet would pass relative to the predicted intercept
+        let aim_error = vec2(1.0, 0.0).rotate(heading()) * aim_intercept.length() - aim_intercept;
+        self.aim_error_graph.add(aim_error.x, aim_error.y);
+        self.aim_error_graph.tick();
+
+        debug_draw::line(
+            DebugCategory::Aim,
             position(),
-            position() + vec2(1.0, 0.0).rotate(heading()) * bullet_intercept.length(),
+            position() + vec2(1.0, 0.0).rotate(heading()) * aim_intercept.length(),
             0x00ff00,
         );
-        draw_line(
+        debug_draw::cone(
+            DebugCategory::Aim,
             position(),
-            position() + bullet_intercept.rotate(-fire_angle_threshold),
+            bullet_intercept_angle,
+            fire_angle_threshold,
+            aim_intercept.length(),
             0xff0000,
         );
-        draw_line(
-            position(),
-            position() + bullet_intercept.rotate(fire_angle_threshold),
+        debug_draw::diamond(
+            DebugCategory::Aim,
+            position() + bullet_intercept,
+            50.0,
             0xff0000,
         );
-        draw_diamond(position() + bullet_intercept, 50.0, 0xff0000);
-        draw_diamond(position() + ship_intercept, 50.0, 0x0000ff);
-        self.target_last_accel = target_accel;
-        self.target_last_velocity = target_velocity();
+        debug_draw::diamond(
+            DebugCategory::Movement,
+            position() + ship_intercept,
+            50.0,
+            0x0000ff,
+        );
+        debug_draw::velocity_arrow(
+            DebugCategory::Tracking,
+            target_position,
+            target_vel,
+            1.0,
+            0xffff00,
+        );
+
+        draw_trajectory(&predict_track_trajectory(
+            &self.own_track,
+            TRAJECTORY_TICKS,
+            TRAJECTORY_STEP,
+        ));
+        draw_trajectory(&predict_track_trajectory(
+            &self.target_track,
+            TRAJECTORY_TICKS,
+            TRAJECTORY_STEP,
+        ));
+
+        //Where the target could be by the time our bullets arrive
+        reachable_envelope(
+            &self.target_track,
+            &class_info(self.target_track.class),
+            (solution.time_of_flight / TICK_LENGTH).round() as i32,
+            ENVELOPE_DIRECTIONS,
+        )
+        .draw(0xff8800);
     }
 
     fn name(&self) -> String {
         return type_name::<Deflection>().into();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_fallback_uses_scenario_target() {
+        let state = fallback_state(FallbackTarget::Scenario, || {
+            (vec2(100.0, 200.0), vec2(1.0, 2.0))
+        });
+        assert_eq!(state, Some((vec2(100.0, 200.0), vec2(1.0, 2.0))));
+    }
+
+    #[test]
+    fn external_fallback_ignores_scenario() {
+        let fallback = FallbackTarget::External {
+            position: vec2(-50.0, 10.0),
+            velocity: vec2(0.0, 5.0),
+        };
+        let state = fallback_state(fallback, || panic!("scenario target read"));
+        assert_eq!(state, Some((vec2(-50.0, 10.0), vec2(0.0, 5.0))));
+    }
+
+    #[test]
+    fn no_fallback_holds_fire() {
+        let state = fallback_state(FallbackTarget::None, || panic!("scenario target read"));
+        assert!(state.is_none());
+    }
+}
@@ -0,0 +1,64 @@
+use oort_api::prelude::*;
+
+use super::ai::AI;
+use crate::class_info::class_info;
+use crate::kinematics::*;
+
+//Stationary target practice (tutorial_guns, tutorial_rotation). Turns to face target() and
+//fires whenever a bullet would pass through it.
+#[derive(Default)]
+pub struct Gunnery {}
+
+impl Gunnery {
+    pub fn new() -> Gunnery {
+        return Default::default();
+    }
+}
+
+impl AI for Gunnery {
+    fn name(&self) -> String {
+        return "Gunnery".into();
+    }
+
+    fn tick(&mut self) {
+        let target_delta = target() - position();
+        let angle_delta = angle_diff(heading(), target_delta.angle());
+        torque(get_turn_accel(
+            angle_delta,
+            angular_velocity(),
+            0.0,
+            max_angular_acceleration(),
+        ));
+
+        let radius = class_info(Class::Target).radius;
+        if should_fire(angle_delta, target_delta.length(), radius) && reload_ticks(0) == 0 {
+            fire(0);
+        }
+    }
+}
+
+//Whether a bullet fired angle_error off the line to a target distance away passes through it
+pub fn should_fire(angle_error: f64, distance: f64, radius: f64) -> bool {
+    return angle_error.abs() <= (radius / distance).atan();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_only_when_on_target() {
+        assert!(should_fire(0.0, 1000.0, 10.0));
+        assert!(should_fire(-0.009, 1000.0, 10.0));
+        assert!(!should_fire(0.02, 1000.0, 10.0));
+        assert!(!should_fire(-0.02, 1000.0, 10.0));
+    }
+
+    #[test]
+    fn firing_window_widens_up_close() {
+        assert!(!should_fire(0.05, 1000.0, 10.0));
+        assert!(should_fire(0.05, 100.0, 10.0));
+        //Inside the target's radius any aim hits
+        assert!(should_fire(1.0, 5.0, 10.0));
+    }
+}
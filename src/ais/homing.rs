@@ -0,0 +1,162 @@
+use oort_api::prelude::*;
+
+use super::ai::AI;
+use crate::kinematics::*;
+use crate::radar::scheduler::RadarScheduler;
+use crate::tracking::{Track, TrackTable};
+
+//Proportional navigation gain
+const NAVIGATION_GAIN: f64 = 4.0;
+//Detonate when this close, or when the target would be passed before next tick
+const BLAST_RADIUS: f64 = 20.0; //m
+const CONTACT_MAX_AGE: u32 = 60;
+
+//Guidance for our missiles and torpedoes. Picks the closest ship on radar and flies
+//proportional navigation at it: lateral acceleration proportional to the closing speed and the
+//line of sight's rotation, with whatever thrust is left pushing down the line of sight. The
+//body is kept pointed along the commanded acceleration so the main engine does the work.
+#[derive(Default)]
+pub struct Homing {
+    contacts: TrackTable,
+    radar: RadarScheduler,
+}
+
+impl Homing {
+    pub fn new() -> Homing {
+        return Default::default();
+    }
+}
+
+fn is_ship(track: &&Track) -> bool {
+    return matches!(
+        track.class,
+        Class::Fighter | Class::Frigate | Class::Cruiser | Class::Target
+    );
+}
+
+impl AI for Homing {
+    fn name(&self) -> String {
+        return "Homing".into();
+    }
+
+    fn tick(&mut self) {
+        self.radar.tick(&mut self.contacts);
+        self.contacts.prune(CONTACT_MAX_AGE);
+
+        let target = self.contacts.tracks.iter().filter(is_ship).min_by(|a, b| {
+            let a_distance = (a.current_position() - position()).length();
+            let b_distance = (b.current_position() - position()).length();
+            a_distance.total_cmp(&b_distance)
+        });
+        self.radar.set_priority(target.map(|track| track.id));
+        let target = match target {
+            Some(target) => target,
+            None => {
+                accelerate(vec2(max_forward_acceleration(), 0.0).rotate(heading()));
+                return;
+            }
+        };
+
+        let relative_pos = target.current_position() - position();
+        let relative_vel = target.velocity - velocity();
+        if should_detonate(relative_pos, relative_vel) {
+            explode();
+            return;
+        }
+
+        let accel = guidance_accel(relative_pos, relative_vel, max_forward_acceleration());
+        let angle_delta = angle_diff(heading(), accel.angle());
+        torque(get_turn_accel(
+            angle_delta,
+            angular_velocity(),
+            0.0,
+            max_angular_acceleration(),
+        ));
+        accelerate(accel);
+    }
+}
+
+fn closing_speed(relative_pos: Vec2, relative_vel: Vec2) -> f64 {
+    return -relative_pos.dot(relative_vel) / relative_pos.length().max(1.0);
+}
+
+//Whether the target is in the blast radius now, or will be passed before next tick
+pub fn should_detonate(relative_pos: Vec2, relative_vel: Vec2) -> bool {
+    let distance = relative_pos.length();
+    let closing_speed = closing_speed(relative_pos, relative_vel);
+    return distance <= BLAST_RADIUS
+        || (closing_speed > 0.0 && distance <= closing_speed * TICK_LENGTH);
+}
+
+//Proportional navigation command, world frame. relative_pos and relative_vel are the target's
+//relative to us.
+pub fn guidance_accel(relative_pos: Vec2, relative_vel: Vec2, max_accel: f64) -> Vec2 {
+    let distance = relative_pos.length().max(1.0);
+    let line_of_sight = relative_pos / distance;
+    let normal = vec2(-line_of_sight.y, line_of_sight.x);
+    let line_of_sight_rate =
+        (relative_pos.x * relative_vel.y - relative_pos.y * relative_vel.x) / (distance * distance);
+
+    let closing_speed = closing_speed(relative_pos, relative_vel);
+    let lateral = (NAVIGATION_GAIN * closing_speed.max(0.0) * line_of_sight_rate)
+        .clamp(-max_accel, max_accel);
+    let along = (max_accel * max_accel - lateral * lateral).sqrt();
+    return line_of_sight * along + normal * lateral;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_ACCEL: f64 = 300.0;
+
+    #[test]
+    fn head_on_target_gets_full_thrust_down_line_of_sight() {
+        let accel = guidance_accel(vec2(1000.0, 0.0), vec2(-100.0, 0.0), MAX_ACCEL);
+        assert!((accel.x - MAX_ACCEL).abs() < 1e-9);
+        assert!(accel.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn crossing_target_is_led() {
+        //Target ahead moving left, so the line of sight turns left and so should we
+        let accel = guidance_accel(vec2(1000.0, 0.0), vec2(-200.0, 100.0), MAX_ACCEL);
+        assert!(accel.y > 0.0);
+        assert!(accel.x > 0.0);
+        assert!((accel.length() - MAX_ACCEL).abs() < 1e-6);
+
+        let mirrored = guidance_accel(vec2(1000.0, 0.0), vec2(-200.0, -100.0), MAX_ACCEL);
+        assert!((mirrored.y + accel.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lateral_command_is_limited() {
+        let accel = guidance_accel(vec2(100.0, 0.0), vec2(-1000.0, 1000.0), MAX_ACCEL);
+        assert!((accel.length() - MAX_ACCEL).abs() < 1e-6);
+    }
+
+    #[test]
+    fn detonates_in_blast_radius() {
+        assert!(should_detonate(
+            vec2(BLAST_RADIUS - 1.0, 0.0),
+            vec2(0.0, 0.0)
+        ));
+        assert!(!should_detonate(
+            vec2(BLAST_RADIUS + 1.0, 0.0),
+            vec2(0.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn detonates_before_passing_target() {
+        //Would be 200 m past it by next tick
+        let closing = vec2(-(BLAST_RADIUS + 50.0 + 200.0) / TICK_LENGTH, 0.0);
+        assert!(should_detonate(vec2(BLAST_RADIUS + 50.0, 0.0), closing));
+        assert!(!should_detonate(vec2(1000.0, 0.0), vec2(-100.0, 0.0)));
+        //Receding targets are never worth it
+        assert!(!should_detonate(
+            vec2(BLAST_RADIUS + 1.0, 0.0),
+            vec2(1000.0, 0.0)
+        ));
+    }
+}
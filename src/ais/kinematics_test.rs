@@ -82,7 +82,7 @@ impl AI for KinematicsTest {
         self.graph1.tick();
 
         accelerate(self.accel);
-        debug!("{}", self.initial_accel);
+        debug_log!("{}", self.initial_accel);
         self.accel += self.initial_jerk * TICK_LENGTH;
     }
 }
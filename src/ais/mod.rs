@@ -1,4 +1,9 @@
 pub mod ai;
-pub mod deflection;
 pub mod ai_selector;
-pub mod kinematics_test;
\ No newline at end of file
+pub mod deflection;
+pub mod gunnery;
+pub mod homing;
+pub mod kinematics_test;
+pub mod radio_target;
+pub mod squadron;
+pub mod waypoint;
@@ -0,0 +1,74 @@
+use oort_api::prelude::*;
+
+use super::ai::AI;
+use super::deflection::{Deflection, FallbackTarget};
+
+//tutorial_radio broadcasts the target on this channel as [x, y, vx, vy]
+const TARGET_CHANNEL: usize = 2;
+
+//Shoots a target whose position and velocity arrive over the radio. Aiming and firing is
+//Deflection's, radar contacts still take priority if it finds any.
+pub struct RadioTarget {
+    deflection: Deflection,
+}
+
+impl RadioTarget {
+    pub fn new() -> RadioTarget {
+        let mut deflection = Deflection::new();
+        deflection.set_fallback_target(FallbackTarget::None);
+        return RadioTarget {
+            deflection: deflection,
+        };
+    }
+}
+
+impl AI for RadioTarget {
+    fn name(&self) -> String {
+        return "RadioTarget".into();
+    }
+
+    fn tick(&mut self) {
+        set_radio_channel(TARGET_CHANNEL);
+        if let Some(message) = receive() {
+            self.deflection.set_fallback_target(decode_target(message));
+        }
+        self.deflection.tick();
+    }
+}
+
+//Target from a [x, y, vx, vy] broadcast, brought forward to this tick
+pub fn decode_target(message: [f64; 4]) -> FallbackTarget {
+    //Message is from last tick
+    let velocity = vec2(message[2], message[3]);
+    return FallbackTarget::External {
+        position: vec2(message[0], message[1]) + velocity * TICK_LENGTH,
+        velocity: velocity,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_and_advances_a_tick() {
+        match decode_target([1000.0, -500.0, 60.0, -120.0]) {
+            FallbackTarget::External { position, velocity } => {
+                assert!((position - vec2(1001.0, -502.0)).length() < 1e-9);
+                assert_eq!(velocity, vec2(60.0, -120.0));
+            }
+            _ => panic!("not an external target"),
+        }
+    }
+
+    #[test]
+    fn stationary_target_stays_put() {
+        assert!(
+            decode_target([10.0, 20.0, 0.0, 0.0])
+                == FallbackTarget::External {
+                    position: vec2(10.0, 20.0),
+                    velocity: vec2(0.0, 0.0),
+                }
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use oort_api::prelude::*;
+
+use super::ai::AI;
+use super::deflection::Deflection;
+use crate::formation::Formation;
+use crate::identity::Identity;
+use crate::radio::mux::RadioMux;
+use crate::target_allocation::TargetAllocation;
+
+//Fighters flying in formation. Everyone aims and shoots as Deflection does, spread across
+//targets by TargetAllocation. The commander also flies as Deflection does, followers override
+//its movement to hold their slot.
+pub struct Squadron {
+    radio: RadioMux,
+    identity: Identity,
+    formation: Formation,
+    allocation: TargetAllocation,
+    deflection: Deflection,
+}
+
+impl Squadron {
+    pub fn new() -> Squadron {
+        return Squadron {
+            radio: RadioMux::new(),
+            identity: Identity::new(),
+            formation: Formation::new(),
+            allocation: TargetAllocation::new(),
+            deflection: Deflection::new(),
+        };
+    }
+}
+
+impl AI for Squadron {
+    fn name(&self) -> String {
+        return "Squadron".into();
+    }
+
+    fn tick(&mut self) {
+        self.radio.tick();
+        self.identity.tick(&mut self.radio);
+        self.formation.tick(&mut self.radio, &mut self.identity);
+
+        self.deflection
+            .set_ally_targets(self.allocation.ally_targets(&self.identity));
+        self.deflection.tick();
+
+        self.allocation.tick(
+            &mut self.radio,
+            &mut self.identity,
+            self.deflection
+                .target()
+                .map(|track| track.current_position()),
+        );
+        self.radio.flush(self.identity.id);
+
+        //Last accelerate call of the tick wins
+        self.formation.hold_slot(&self.identity);
+        debug_log!(
+            "id: {} rank: {:?} commander: {:?}",
+            self.identity.id,
+            self.identity.rank(),
+            self.identity.commander()
+        );
+    }
+}
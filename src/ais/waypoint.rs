@@ -0,0 +1,129 @@
+use oort_api::prelude::*;
+
+use super::ai::AI;
+use crate::debug_draw::{self, DebugCategory};
+use crate::kinematics::*;
+
+//Flies to target() and stops there (tutorial_acceleration, tutorial_acceleration2). Faces the
+//target so the strong forward thruster speeds it up, the stop is planned around the braking limit.
+#[derive(Default)]
+pub struct Waypoint {}
+
+impl Waypoint {
+    pub fn new() -> Waypoint {
+        return Default::default();
+    }
+}
+
+impl AI for Waypoint {
+    fn name(&self) -> String {
+        return "Waypoint".into();
+    }
+
+    fn tick(&mut self) {
+        let target_delta = target() - position();
+        let angle_delta = angle_diff(heading(), target_delta.angle());
+        torque(get_turn_accel(
+            angle_delta,
+            angular_velocity(),
+            0.0,
+            max_angular_acceleration(),
+        ));
+
+        accelerate(waypoint_accel(
+            target_delta,
+            velocity(),
+            max_forward_acceleration(),
+            max_braking_acceleration(),
+        ));
+        debug_draw::line(DebugCategory::Movement, position(), target(), 0x00ff00);
+    }
+}
+
+//As get_arrive_accel, but speeding up towards delta can use forward_accel. Stopping is still
+//limited to braking_accel.
+pub fn waypoint_accel(delta: Vec2, velocity: Vec2, forward_accel: f64, braking_accel: f64) -> Vec2 {
+    let distance = delta.length();
+    if distance <= 0.0 {
+        return get_arrive_accel(delta, velocity, braking_accel);
+    }
+
+    let desired_velocity =
+        delta / distance * get_optimal_arrive_velocity(distance, braking_accel, 0.0);
+    let accel = (desired_velocity - velocity) / TICK_LENGTH;
+    let limit = if accel.dot(delta) > 0.0 {
+        forward_accel
+    } else {
+        braking_accel
+    };
+    if accel.length() > limit {
+        return accel.normalize() * limit;
+    }
+    return accel;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORWARD_ACCEL: f64 = 60.0;
+    const BRAKING_ACCEL: f64 = 30.0;
+
+    //Flies Waypoint's controller from rest, returns the final position, velocity and the tick it
+    //first got within a metre
+    fn fly(target: Vec2, forward_accel: f64) -> (Vec2, Vec2, Option<u32>) {
+        let mut position = vec2(0.0, 0.0);
+        let mut velocity = vec2(0.0, 0.0);
+        let mut arrived = None;
+        for tick in 0..60 * 60 {
+            let accel = waypoint_accel(target - position, velocity, forward_accel, BRAKING_ACCEL);
+            assert!(accel.length() <= forward_accel + 1e-9);
+            velocity += accel * TICK_LENGTH;
+            position += velocity * TICK_LENGTH;
+            if arrived.is_none() && (target - position).length() < 1.0 {
+                arrived = Some(tick);
+            }
+        }
+        return (position, velocity, arrived);
+    }
+
+    #[test]
+    fn speeds_up_with_forward_thrust() {
+        let accel = waypoint_accel(
+            vec2(1000.0, 0.0),
+            vec2(0.0, 0.0),
+            FORWARD_ACCEL,
+            BRAKING_ACCEL,
+        );
+        assert!((accel.x - FORWARD_ACCEL).abs() < 1e-9);
+        assert!(accel.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn brakes_at_braking_limit() {
+        //100 m out closing at 200 m/s can't stop in time, braking can't use the forward thruster
+        let accel = waypoint_accel(
+            vec2(100.0, 0.0),
+            vec2(200.0, 0.0),
+            FORWARD_ACCEL,
+            BRAKING_ACCEL,
+        );
+        assert!((accel.x + BRAKING_ACCEL).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arrives_and_stops_sooner_than_braking_limit_alone() {
+        for target in [vec2(1000.0, 0.0), vec2(-300.0, 2000.0)] {
+            let (position, velocity, arrived) = fly(target, FORWARD_ACCEL);
+            assert!(
+                (position - target).length() < 1.0,
+                "ended at {:?}",
+                position
+            );
+            assert!(velocity.length() < 1.0, "still moving at {:?}", velocity);
+
+            let (_, _, arrived_braking_only) = fly(target, BRAKING_ACCEL);
+            assert!(arrived.unwrap() < arrived_braking_only.unwrap());
+        }
+    }
+}
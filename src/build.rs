@@ -1,3 +1,5 @@
+use std::env;
+use std::fs;
 use std::path::Path;
 extern crate rustsourcebundler;
 use rustsourcebundler::Bundler;
@@ -7,5 +9,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Bundler::new(Path::new("src/bundle_input.rs"), Path::new("target/bundle_output.rs"));
     bundler.crate_name("oort_ai"); // again this must match the name in Cargo.toml
     bundler.run();
+
+    //Oort compiles the bundle without cargo features, so resolve debug-overlay here or it would
+    //always be stripped from the uploaded code. all() is always true, any() never is.
+    let enabled = env::var_os("CARGO_FEATURE_DEBUG_OVERLAY").is_some();
+    let output = Path::new("target/bundle_output.rs");
+    let bundle = fs::read_to_string(output)?;
+    let mut resolved = bundle.replace(
+        r#"feature = "debug-overlay""#,
+        if enabled { "all()" } else { "any()" },
+    );
+
+    //The radio key in the source is public, so each bundle can be given its own. Listing the env
+    //var turns off cargo's rerun on any change, so the sources have to be listed too.
+    println!("cargo:rerun-if-env-changed=OORT_TEAM_KEY");
+    println!("cargo:rerun-if-changed=src");
+    if let Ok(key) = env::var("OORT_TEAM_KEY") {
+        let key = match key.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(&hex.replace('_', ""), 16)?,
+            None => key.replace('_', "").parse::<u64>()?,
+        };
+        let default = "pub const TEAM_KEY: u64 = 0x6f6f_7274_5f61_6921;";
+        if !resolved.contains(default) {
+            return Err("TEAM_KEY not found in the bundle".into());
+        }
+        resolved = resolved.replace(default, &format!("pub const TEAM_KEY: u64 = {:#x};", key));
+    }
+
+    fs::write(output, resolved)?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,151 @@
+use oort_api::prelude::*;
+
+//Approximate physical limits of each ship class, mirrored from the game's ship definitions.
+//Needed for contacts, where the max_*_acceleration() functions only describe our own ship.
+#[derive(Clone, Copy)]
+pub struct ClassInfo {
+    pub radius: f64,             //m, rough hit radius
+    pub max_forward_accel: f64,  //m/s^2
+    pub max_backward_accel: f64, //m/s^2
+    pub max_lateral_accel: f64,  //m/s^2
+    pub max_angular_accel: f64,  //rad/s^2
+}
+
+impl ClassInfo {
+    //Largest acceleration available in any direction
+    pub fn max_accel(&self) -> f64 {
+        return self
+            .max_forward_accel
+            .max(self.max_backward_accel)
+            .max(self.max_lateral_accel);
+    }
+}
+
+pub fn class_info(class: Class) -> ClassInfo {
+    return match class {
+        Class::Fighter => ClassInfo {
+            radius: 10.0,
+            max_forward_accel: 60.0,
+            max_backward_accel: 30.0,
+            max_lateral_accel: 30.0,
+            max_angular_accel: TAU,
+        },
+        Class::Frigate => ClassInfo {
+            radius: 50.0,
+            max_forward_accel: 10.0,
+            max_backward_accel: 5.0,
+            max_lateral_accel: 5.0,
+            max_angular_accel: TAU / 8.0,
+        },
+        Class::Cruiser => ClassInfo {
+            radius: 100.0,
+            max_forward_accel: 5.0,
+            max_backward_accel: 2.5,
+            max_lateral_accel: 2.5,
+            max_angular_accel: TAU / 16.0,
+        },
+        Class::Missile => ClassInfo {
+            radius: 3.0,
+            max_forward_accel: 300.0,
+            max_backward_accel: 0.0,
+            max_lateral_accel: 100.0,
+            max_angular_accel: 4.0 * TAU,
+        },
+        Class::Torpedo => ClassInfo {
+            radius: 5.0,
+            max_forward_accel: 70.0,
+            max_backward_accel: 0.0,
+            max_lateral_accel: 20.0,
+            max_angular_accel: 2.0 * TAU,
+        },
+        Class::Target => ClassInfo {
+            radius: 10.0,
+            max_forward_accel: 0.0,
+            max_backward_accel: 0.0,
+            max_lateral_accel: 0.0,
+            max_angular_accel: 0.0,
+        },
+        Class::Asteroid => ClassInfo {
+            radius: 50.0,
+            max_forward_accel: 0.0,
+            max_backward_accel: 0.0,
+            max_lateral_accel: 0.0,
+            max_angular_accel: 0.0,
+        },
+        //Assume a fighter, the most agile thing we are likely to shoot at
+        _ => class_info(Class::Fighter),
+    };
+}
+
+//Missile and torpedo launcher weapon indices, mirrored from the game's ship definitions
+pub fn class_launchers(class: Class) -> Vec<usize> {
+    return match class {
+        Class::Fighter => vec![1],
+        Class::Frigate => vec![3],
+        Class::Cruiser => vec![1, 2, 3],
+        _ => vec![],
+    };
+}
+
+#[derive(Clone, Copy)]
+pub struct AbilitySpec {
+    pub ability: Ability,
+    pub duration: u32, //Ticks the ability stays active
+    pub cooldown: u32, //Ticks from activation until it can be used again
+}
+
+//Approximate timings mirrored from the game's ship definitions, in ticks
+pub fn class_abilities(class: Class) -> Vec<AbilitySpec> {
+    let seconds = |s: f64| (s / TICK_LENGTH).round() as u32;
+    return match class {
+        Class::Fighter => vec![AbilitySpec {
+            ability: Ability::Boost,
+            duration: seconds(2.0),
+            cooldown: seconds(10.0),
+        }],
+        Class::Frigate | Class::Cruiser => vec![AbilitySpec {
+            ability: Ability::Shield,
+            duration: seconds(1.0),
+            cooldown: seconds(5.0),
+        }],
+        Class::Missile => vec![AbilitySpec {
+            ability: Ability::Boost,
+            duration: seconds(2.0),
+            cooldown: seconds(10.0),
+        }],
+        Class::Torpedo => vec![AbilitySpec {
+            ability: Ability::Decoy,
+            duration: seconds(0.5),
+            cooldown: seconds(10.0),
+        }],
+        _ => vec![],
+    };
+}
+
+#[derive(Clone, Copy)]
+pub struct TurretSpec {
+    pub index: usize,
+    pub bullet_speed: f64, //m/s
+}
+
+//Turrets usable for point defense, approximate values mirrored from the game's ship definitions.
+//Fixed guns and missile launchers are left out.
+pub fn class_turrets(class: Class) -> Vec<TurretSpec> {
+    return match class {
+        Class::Frigate => vec![
+            TurretSpec {
+                index: 1,
+                bullet_speed: 1000.0,
+            },
+            TurretSpec {
+                index: 2,
+                bullet_speed: 1000.0,
+            },
+        ],
+        Class::Cruiser => vec![TurretSpec {
+            index: 0,
+            bullet_speed: 2000.0,
+        }],
+        _ => vec![],
+    };
+}
@@ -0,0 +1,187 @@
+use oort_api::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+//Reusable in-world debug drawing. Everything drawn through here can be switched off globally
+//(eg for tournament bundles) or per category. Call as debug_draw::cone(...) etc.
+//Without the debug-overlay feature every function is a no-op and the drawing code is compiled out.
+
+pub const COMPILED_IN: bool = cfg!(feature = "debug-overlay");
+
+const CIRCLE_SEGMENTS: i32 = 24;
+const ARROW_HEAD_SIZE: f64 = 0.2; //Fraction of arrow length
+const ARROW_HEAD_MAX: f64 = 40.0; //World units
+const LABEL_OFFSET: f64 = 20.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DebugCategory {
+    Aim = 1 << 0,        //Fire cones, intercept points
+    Movement = 1 << 1,   //Own velocity/acceleration vectors
+    Tracking = 1 << 2,   //Track boxes, target velocity
+    Prediction = 1 << 3, //Predicted trajectories
+    Radar = 1 << 4,
+    Radio = 1 << 5,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+static ENABLED_CATEGORIES: AtomicU32 = AtomicU32::new(u32::MAX);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    return COMPILED_IN && ENABLED.load(Ordering::Relaxed);
+}
+
+pub fn set_category_enabled(category: DebugCategory, enabled: bool) {
+    if enabled {
+        ENABLED_CATEGORIES.fetch_or(category as u32, Ordering::Relaxed);
+    } else {
+        ENABLED_CATEGORIES.fetch_and(!(category as u32), Ordering::Relaxed);
+    }
+}
+
+pub fn is_category_enabled(category: DebugCategory) -> bool {
+    return is_enabled() && ENABLED_CATEGORIES.load(Ordering::Relaxed) & category as u32 != 0;
+}
+
+pub fn line(category: DebugCategory, start: Vec2, end: Vec2, color: u32) {
+    if !is_category_enabled(category) {
+        return;
+    }
+    draw_line(start, end, color);
+}
+
+pub fn diamond(category: DebugCategory, center: Vec2, size: f64, color: u32) {
+    if !is_category_enabled(category) {
+        return;
+    }
+    draw_diamond(center, size, color);
+}
+
+pub fn circle(category: DebugCategory, center: Vec2, radius: f64, color: u32) {
+    arc(category, center, radius, 0.0, TAU, color);
+}
+
+//Angles in radians, counter clockwise from start_angle to end_angle
+pub fn arc(
+    category: DebugCategory,
+    center: Vec2,
+    radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+    color: u32,
+) {
+    if !is_category_enabled(category) {
+        return;
+    }
+
+    let sweep = end_angle - start_angle;
+    let segments = ((sweep.abs() / TAU * CIRCLE_SEGMENTS as f64).ceil() as i32).max(1);
+    let step = sweep / segments as f64;
+
+    let mut last_point = center + vec2(radius, 0.0).rotate(start_angle);
+    for i in 1..=segments {
+        let point = center + vec2(radius, 0.0).rotate(start_angle + step * i as f64);
+        draw_line(last_point, point, color);
+        last_point = point;
+    }
+}
+
+//Two edge lines and an arc joining them. half_angle is either side of heading.
+pub fn cone(
+    category: DebugCategory,
+    origin: Vec2,
+    heading: f64,
+    half_angle: f64,
+    length: f64,
+    color: u32,
+) {
+    if !is_category_enabled(category) {
+        return;
+    }
+
+    draw_line(
+        origin,
+        origin + vec2(length, 0.0).rotate(heading - half_angle),
+        color,
+    );
+    draw_line(
+        origin,
+        origin + vec2(length, 0.0).rotate(heading + half_angle),
+        color,
+    );
+    arc(
+        category,
+        origin,
+        length,
+        heading - half_angle,
+        heading + half_angle,
+        color,
+    );
+}
+
+pub fn arrow(category: DebugCategory, start: Vec2, end: Vec2, color: u32) {
+    if !is_category_enabled(category) {
+        return;
+    }
+
+    let delta = end - start;
+    let length = delta.length();
+    if length <= 0.0 {
+        return;
+    }
+
+    let head_size = (length * ARROW_HEAD_SIZE).min(ARROW_HEAD_MAX);
+    let back = delta * (-head_size / length);
+    draw_line(start, end, color);
+    draw_line(end, end + back.rotate(TAU / 12.0), color);
+    draw_line(end, end + back.rotate(-TAU / 12.0), color);
+}
+
+//Arrow from position showing where velocity would carry it in the given number of seconds
+pub fn velocity_arrow(
+    category: DebugCategory,
+    position: Vec2,
+    velocity: Vec2,
+    seconds: f64,
+    color: u32,
+) {
+    arrow(category, position, position + velocity * seconds, color);
+}
+
+pub fn polyline(category: DebugCategory, points: &[Vec2], color: u32) {
+    if !is_category_enabled(category) {
+        return;
+    }
+
+    for pair in points.windows(2) {
+        draw_line(pair[0], pair[1], color);
+    }
+}
+
+//Axis aligned box around a tracked contact with a label above it. Labels are expensive.
+pub fn track_box(category: DebugCategory, center: Vec2, size: f64, label: &str, color: u32) {
+    if !is_category_enabled(category) {
+        return;
+    }
+
+    let half = size * 0.5;
+    let corners = [
+        center + vec2(-half, -half),
+        center + vec2(half, -half),
+        center + vec2(half, half),
+        center + vec2(-half, half),
+        center + vec2(-half, -half),
+    ];
+    polyline(category, &corners, color);
+
+    if !label.is_empty() {
+        draw_text!(
+            center + vec2(-half, half + LABEL_OFFSET),
+            color,
+            "{}",
+            label
+        );
+    }
+}
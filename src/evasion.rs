@@ -0,0 +1,174 @@
+use oort_api::prelude::*;
+
+use crate::class_info::class_info;
+use crate::constants::*;
+use crate::kinematics::*;
+use crate::tracking::{Track, TrackTable};
+
+//Threats arriving later than this are ignored, there is time to react later
+const MAX_THREAT_TIME: f64 = 3.0; //s
+
+//Ignore gun platforms further than this, their bullets take too long to matter
+const MAX_GUN_RANGE: f64 = 3000.0; //m
+
+//Keep a chosen dodge direction for at least this long so we don't dither. Following the
+//caller's preferred lateral isn't held, Jinking already times its own changes.
+const MIN_DODGE_TICKS: u32 = 20;
+
+pub struct Threat {
+    pub time_to_impact: f64, //s
+    pub miss_distance: f64,  //m, if we keep doing what we're doing
+    pub risk: f64,           //0-1
+}
+
+//Dodges incoming missiles and enemy gunfire by accelerating sideways relative to our heading,
+//so the guns stay roughly on target. Candidate lateral accelerations are scored by how close
+//each threat's predicted path passes to where that acceleration would put us. The caller's
+//preferred lateral (eg from Jinking) is flown unless another candidate is clearly safer.
+#[derive(Default)]
+pub struct Evasion {
+    pub risk: f64,     //Total risk of the lateral being flown
+    pub lateral: f64,  //Chosen lateral acceleration as a fraction of max, -1 (right) to 1 (left)
+    pub dodging: bool, //Flying something other than the preferred lateral
+    last_change_tick: u32,
+}
+
+impl Evasion {
+    pub fn new() -> Evasion {
+        return Default::default();
+    }
+
+    //preferred_lateral is a fraction of max, -1 (right) to 1 (left).
+    //Returns the acceleration to add to the movement command, world frame
+    pub fn tick(&mut self, contacts: &TrackTable, preferred_lateral: f64) -> Vec2 {
+        const CANDIDATES: [f64; 3] = [-1.0, 0.0, 1.0];
+        const RISK_MARGIN: f64 = 0.05;
+
+        let mut best_lateral = preferred_lateral;
+        let mut best_risk = self.total_risk(contacts, preferred_lateral);
+        for lateral in CANDIDATES {
+            let risk = self.total_risk(contacts, lateral);
+            //Prefer the requested choice on ties so the dodge isn't a coin flip every tick
+            if risk < best_risk - RISK_MARGIN {
+                best_risk = risk;
+                best_lateral = lateral;
+            }
+        }
+
+        let following = !self.dodging && best_lateral == preferred_lateral;
+        let held = current_tick() - self.last_change_tick < MIN_DODGE_TICKS;
+        if best_lateral != self.lateral && (following || !held) {
+            self.lateral = best_lateral;
+            self.dodging = best_lateral != preferred_lateral;
+            self.last_change_tick = current_tick();
+        }
+        //The preferred lateral can come round to the dodge we were already flying
+        if self.lateral == preferred_lateral {
+            self.dodging = false;
+        }
+        //What we're actually flying, which isn't the best candidate while a switch is held off
+        self.risk = self.total_risk(contacts, self.lateral);
+
+        return vec2(0.0, self.lateral * max_lateral_acceleration()).rotate(heading());
+    }
+
+    fn total_risk(&self, contacts: &TrackTable, lateral: f64) -> f64 {
+        let accel = vec2(0.0, lateral * max_lateral_acceleration()).rotate(heading());
+        let mut risk = 0.0;
+        for track in &contacts.tracks {
+            if let Some(threat) = assess_threat(track, accel) {
+                risk += threat.risk;
+            }
+        }
+        return risk;
+    }
+}
+
+//The missile or torpedo that would reach us soonest if we stopped maneuvering
+pub fn most_imminent_missile(contacts: &TrackTable) -> Option<Threat> {
+    let mut most_imminent: Option<Threat> = None;
+    for track in &contacts.tracks {
+        if track.class != Class::Missile && track.class != Class::Torpedo {
+            continue;
+        }
+        if let Some(threat) = assess_missile(track, vec2(0.0, 0.0)) {
+            match &most_imminent {
+                Some(best) if best.time_to_impact <= threat.time_to_impact => {}
+                _ => most_imminent = Some(threat),
+            }
+        }
+    }
+    return most_imminent;
+}
+
+//How dangerous a contact is if we hold accel from now on
+pub fn assess_threat(track: &Track, accel: Vec2) -> Option<Threat> {
+    return match track.class {
+        Class::Missile | Class::Torpedo => assess_missile(track, accel),
+        Class::Fighter | Class::Frigate | Class::Cruiser => assess_gun(track, accel),
+        _ => None,
+    };
+}
+
+//Missiles are assumed to keep their current acceleration. Risk is how close they get.
+fn assess_missile(track: &Track, accel: Vec2) -> Option<Threat> {
+    let relative_pos = track.current_position() - position();
+    let relative_vel = track.velocity - velocity();
+    let closing_speed = -relative_pos.dot(relative_vel) / relative_pos.length().max(1.0);
+    if closing_speed <= 0.0 {
+        return None;
+    }
+
+    let time_to_impact = relative_pos.length() / closing_speed;
+    if time_to_impact > MAX_THREAT_TIME {
+        return None;
+    }
+
+    let ticks = (time_to_impact / TICK_LENGTH).round() as i32;
+    let missile_pos = track.current_position()
+        + vec2(
+            delta_distance_iterative(ticks, track.velocity.x, track.accel.x, 0.0),
+            delta_distance_iterative(ticks, track.velocity.y, track.accel.y, 0.0),
+        );
+    let our_pos = position()
+        + vec2(
+            delta_distance_iterative(ticks, velocity().x, accel.x, 0.0),
+            delta_distance_iterative(ticks, velocity().y, accel.y, 0.0),
+        );
+    let miss_distance = (missile_pos - our_pos).length();
+    let danger_radius = class_info(class()).radius + class_info(track.class).radius;
+
+    return Some(Threat {
+        time_to_impact: time_to_impact,
+        miss_distance: miss_distance,
+        risk: proximity_risk(miss_distance, danger_radius),
+    });
+}
+
+//Gun platforms are assumed to lead us perfectly under constant velocity. Risk is how close our
+//actual position at bullet arrival is to that lead point.
+fn assess_gun(track: &Track, accel: Vec2) -> Option<Threat> {
+    let distance = (track.current_position() - position()).length();
+    if distance > MAX_GUN_RANGE {
+        return None;
+    }
+
+    let time_to_impact = distance / BULLET_SPEED;
+    let ticks = (time_to_impact / TICK_LENGTH).round() as i32;
+    //Deviation from a constant velocity prediction is just the displacement due to accel
+    let deviation = vec2(
+        delta_distance_iterative(ticks, 0.0, accel.x, 0.0),
+        delta_distance_iterative(ticks, 0.0, accel.y, 0.0),
+    )
+    .length();
+
+    return Some(Threat {
+        time_to_impact: time_to_impact,
+        miss_distance: deviation,
+        risk: proximity_risk(deviation, class_info(class()).radius),
+    });
+}
+
+fn proximity_risk(miss_distance: f64, radius: f64) -> f64 {
+    return (-(miss_distance / radius.max(1.0)).powf(2.0)).exp();
+}
@@ -0,0 +1,114 @@
+use oort_api::prelude::*;
+
+use crate::class_info::class_info;
+use crate::constants::*;
+use crate::tracking::Track;
+
+//Miss distance (m, 1 sigma) we expect even against a perfectly predictable target.
+//Covers turret/heading quantisation and the bullet's own spread.
+pub const BASE_MISS_SIGMA: f64 = 2.0;
+const DEFAULT_THRESHOLD: f64 = 0.2;
+
+pub struct FiringSolution {
+    pub hit_probability: f64,
+    pub time_of_flight: f64, //s
+    pub miss_distance: f64,  //m, perpendicular distance the bullet passes from the aim point
+    pub miss_sigma: f64,     //m, 1 sigma uncertainty of where the target will be
+    pub target_radius: f64,  //m
+}
+
+//Decides whether to fire by estimating the probability a bullet fired now hits,
+//and keeps count of the hits expected from the shots taken.
+pub struct FireControl {
+    pub threshold: f64, //Minimum hit probability to fire
+    pub shots: u32,
+    pub expected_hits: f64,
+}
+
+impl Default for FireControl {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            shots: 0,
+            expected_hits: 0.0,
+        }
+    }
+}
+
+impl FireControl {
+    pub fn new() -> FireControl {
+        return Default::default();
+    }
+
+    //aim_error is the angle between where the gun points and the predicted intercept,
+    //intercept is the predicted intercept relative to us.
+    pub fn evaluate(&self, aim_error: f64, intercept: Vec2, target: &Track) -> FiringSolution {
+        let distance = intercept.length();
+        let time_of_flight = distance / BULLET_SPEED;
+        let target_radius = class_info(target.class).radius;
+
+        //Where the target could be instead, from how much it maneuvers beyond our model
+        let maneuver_sigma = 0.5 * target.accel_error * time_of_flight.powf(2.0);
+        let miss_sigma = (BASE_MISS_SIGMA.powf(2.0) + maneuver_sigma.powf(2.0)).sqrt();
+        let miss_distance = aim_error.sin().abs() * distance;
+
+        return FiringSolution {
+            hit_probability: hit_probability(miss_distance, miss_sigma, target_radius),
+            time_of_flight: time_of_flight,
+            miss_distance: miss_distance,
+            miss_sigma: miss_sigma,
+            target_radius: target_radius,
+        };
+    }
+
+    pub fn should_fire(&self, solution: &FiringSolution) -> bool {
+        return solution.hit_probability >= self.threshold;
+    }
+
+    //Call whenever a bullet is actually fired on this solution
+    pub fn record_shot(&mut self, solution: &FiringSolution) {
+        self.shots += 1;
+        self.expected_hits += solution.hit_probability;
+    }
+
+    pub fn expected_hits_per_bullet(&self) -> f64 {
+        if self.shots == 0 {
+            return 0.0;
+        }
+        return self.expected_hits / self.shots as f64;
+    }
+}
+
+//Probability that a bullet aimed miss_distance from the target's centre passes within radius,
+//when the target's true position is normally distributed with miss_sigma across the line of fire.
+pub fn hit_probability(miss_distance: f64, miss_sigma: f64, radius: f64) -> f64 {
+    if miss_sigma <= 0.0 {
+        return if miss_distance.abs() <= radius {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    return normal_cdf((radius - miss_distance) / miss_sigma)
+        - normal_cdf((-radius - miss_distance) / miss_sigma);
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    return 0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2));
+}
+
+//Abramowitz and Stegun 7.1.26, max error 1.5e-7
+pub fn erf(x: f64) -> f64 {
+    const A1: f64 = 0.254829592;
+    const A2: f64 = -0.284496736;
+    const A3: f64 = 1.421413741;
+    const A4: f64 = -1.453152027;
+    const A5: f64 = 1.061405429;
+    const P: f64 = 0.3275911;
+
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + P * x);
+    let y = 1.0 - (((((A5 * t + A4) * t) + A3) * t + A2) * t + A1) * t * (-x * x).exp();
+    return sign * y;
+}
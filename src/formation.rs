@@ -0,0 +1,121 @@
+use oort_api::prelude::*;
+
+use crate::debug_draw::{self, DebugCategory};
+use crate::identity::Identity;
+use crate::kinematics::*;
+use crate::radio::mux::*;
+
+//Message tags on STREAM_FORMATION
+const MSG_LEADER: u8 = 0; //[x, y, heading]
+
+const DEFAULT_SPACING: f64 = 100.0; //m
+
+//Flies a vee behind the fleet commander agreed by Identity. The leader broadcasts its position
+//and heading whenever the radio is free. Followers take slots in rank order and hold them
+//relative to the leader's heading and velocity. If the leader dies Identity promotes the next
+//ship and everyone closes up.
+pub struct Formation {
+    pub spacing: f64,
+    leader: Option<u16>, //ID of the leader the state below came from
+    leader_position: Vec2,
+    leader_velocity: Vec2,
+    leader_heading: f64,
+    last_leader_tick: Option<u32>,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Self {
+            spacing: DEFAULT_SPACING,
+            leader: None,
+            leader_position: vec2(0.0, 0.0),
+            leader_velocity: vec2(0.0, 0.0),
+            leader_heading: 0.0,
+            last_leader_tick: None,
+        }
+    }
+}
+
+impl Formation {
+    pub fn new() -> Formation {
+        return Default::default();
+    }
+
+    //Call every tick after Identity
+    pub fn tick(&mut self, radio: &mut RadioMux, identity: &mut Identity) {
+        let leader = identity.commander();
+        if leader != self.leader {
+            //New leader, don't mix its state with the old one's
+            self.leader = leader;
+            self.last_leader_tick = None;
+        }
+
+        if let (Some(frame), Some(leader)) = (radio.received(STREAM_FORMATION), leader) {
+            if frame.tag == MSG_LEADER && frame.sender == leader && leader != identity.id {
+                identity.heard(leader);
+                self.handle_leader(frame.payload);
+            }
+        }
+
+        //Odd ticks are left for TargetAllocation's claims
+        if identity.is_commander() && current_tick() % 2 == 0 {
+            radio.queue(
+                STREAM_FORMATION,
+                MSG_LEADER,
+                [position().x, position().y, heading()],
+            );
+        }
+    }
+
+    fn handle_leader(&mut self, payload: [f64; 3]) {
+        let leader_position = vec2(payload[0], payload[1]);
+        if let Some(last_tick) = self.last_leader_tick {
+            let elapsed = (current_tick() - last_tick).max(1) as f64 * TICK_LENGTH;
+            self.leader_velocity = (leader_position - self.leader_position) / elapsed;
+        } else {
+            self.leader_velocity = vec2(0.0, 0.0);
+        }
+        self.leader_position = leader_position;
+        self.leader_heading = payload[2];
+        self.last_leader_tick = Some(current_tick());
+    }
+
+    //World position and velocity of our slot, if we're following a leader we've heard from
+    pub fn slot(&self, identity: &Identity) -> Option<(Vec2, Vec2)> {
+        let slot = match identity.rank() {
+            Some(0) | None => return None,
+            Some(rank) => rank,
+        };
+        let last_tick = self.last_leader_tick?;
+
+        //Leader's message is from last tick
+        let age = (current_tick() - last_tick + 1) as f64 * TICK_LENGTH;
+        let leader_position = self.leader_position + self.leader_velocity * age;
+        let offset = slot_offset(slot, self.spacing).rotate(self.leader_heading);
+        return Some((leader_position + offset, self.leader_velocity));
+    }
+
+    //Accelerates towards our slot. Returns false if there's no slot to hold.
+    pub fn hold_slot(&self, identity: &Identity) -> bool {
+        let (slot_position, slot_velocity) = match self.slot(identity) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        accelerate(get_arrive_accel(
+            slot_position - position(),
+            velocity() - slot_velocity,
+            max_braking_acceleration(),
+        ));
+        debug_draw::diamond(DebugCategory::Movement, slot_position, 20.0, 0x00ffff);
+        return true;
+    }
+}
+
+//Slot position relative to the leader, leader frame (x forward). Slots alternate left and
+//right starting with 1, each pair one row further back.
+pub fn slot_offset(slot: usize, spacing: f64) -> Vec2 {
+    let row = ((slot + 1) / 2) as f64;
+    let side = if slot % 2 == 1 { 1.0 } else { -1.0 };
+    return vec2(-row * spacing, side * row * spacing);
+}
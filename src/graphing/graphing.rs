@@ -1,6 +1,8 @@
 use oort_api::prelude::*;
 use std::{collections::VecDeque, fmt::Display};
 
+use super::statistics::RollingStats;
+
 pub trait F64Ex {
     fn move_towards(self, target: f64, max_delta: f64) -> f64;
     fn lerp(self, min: f64, max: f64) -> f64;
@@ -30,8 +32,9 @@ impl F64Ex for f64 {
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct Datum {
-    pub tick: i32,
+    pub tick: f64, //Fractional so points trimmed at the left edge keep their exact time
     pub value: f64,
 }
 
@@ -58,9 +61,25 @@ pub struct Graph {
     pub auto_shrink: bool, //Determines whether graph should shrink in min and max to fit data
     pub smooth_shrink_grow: bool,
     pub debug: bool, //Debug prints lines drawn/max lines drawn (shows line draw savings)
+    pub decimation: Decimation, //How the curve is reduced before drawing. See Decimation.
+    pub show_stats: bool, //Draw mean, std dev, rms and peak over the timespan. Costs a draw_text per tick.
+    pub show_stat_bands: bool, //Draw mean and mean ± std dev lines across the graph
 
-    //Don't set this. Haven't figured out private fields yet.
+    //Don't set these. Haven't figured out private fields yet.
     pub data: VecDeque<Datum>,
+    pub stats: RollingStats, //From every value added while shown, not the decimated curve
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Decimation {
+    //Points within epsilon_squared of the last segment are merged as they are added.
+    //Cheap, but the number of lines drawn depends on the data.
+    Epsilon,
+    //Every point is kept and the curve is reduced at draw time to the min and max of evenly
+    //spaced time buckets. Never draws more than max_lines lines per tick in total: the axes and
+    //stat bands come out of the budget first and the curve and its markers get the rest, so
+    //several graphs can share Oort's line limit. Text isn't counted, it has its own limit.
+    Budget { max_lines: usize },
 }
 
 impl Default for Graph {
@@ -79,7 +98,11 @@ impl Default for Graph {
             auto_shrink: true,
             smooth_shrink_grow: false,
             debug: false,
+            decimation: Decimation::Epsilon,
+            show_stats: false,
+            show_stat_bands: false,
             data: VecDeque::new(),
+            stats: RollingStats::new(),
         }
     }
 }
@@ -88,23 +111,48 @@ impl Graph {
     pub fn new() -> Graph {
         return Default::default();
     }
+}
+
+//No-op stand-ins so call sites compile unchanged when the debug-overlay feature is off
+#[cfg(not(feature = "debug-overlay"))]
+impl Graph {
+    pub fn add(&mut self, _value: f64) {}
+    pub fn tick(&mut self) {}
+}
 
+#[cfg(feature = "debug-overlay")]
+impl Graph {
+    //NaN breaks the curve until the next finite value. Infinite and out of range values are
+    //clamped to the edge of the graph and drawn with a marker.
     pub fn add(&mut self, value: f64) {
-        let mut datum = Datum {
-            tick: current_tick() as i32,
+        let datum = Datum {
+            tick: current_tick() as f64,
             value: value,
         };
 
         if datum.value.is_nan() {
-            datum.value = 0.0;
+            //One gap datum is enough to break the curve
+            if let Some(last) = self.data.back() {
+                if !last.value.is_nan() {
+                    self.data.push_back(datum);
+                }
+            }
+            return;
         }
 
-        if self.data.len() >= 3 {
+        if self.has_stats() {
+            self.stats.push(current_tick() as i32, datum.value);
+        }
+
+        if self.decimation == Decimation::Epsilon
+            && self.data.len() >= 3
+            && self.can_merge_into_last(&datum)
+        {
             let datum_world_position = self.get_datum_world_position(&datum);
             let line_start = self.get_datum_world_position(&self.data[self.data.len() - 3]);
             let line_end = self.get_datum_world_position(&self.data[self.data.len() - 2]);
             let distance_from_line =
-                Graph::point_distance_to_line_squared(datum_world_position, line_start, line_end);
+                point_distance_to_line_squared(datum_world_position, line_start, line_end);
 
             //If change from last point is insignificant, move current point rather than adding a new one.
             if distance_from_line < self.epsilon_squared {
@@ -117,19 +165,41 @@ impl Graph {
         self.data.push_back(datum);
     }
 
+    //Stats cost a sample per value, only keep them if something draws them
+    fn has_stats(&self) -> bool {
+        return self.show_stats || self.show_stat_bands;
+    }
+
+    //Gaps and infinities must keep their own datum so they are still drawn after decimation
+    fn can_merge_into_last(&self, datum: &Datum) -> bool {
+        let len = self.data.len();
+        return datum.value.is_finite()
+            && self.data[len - 1].value.is_finite()
+            && self.data[len - 2].value.is_finite()
+            && self.data[len - 3].value.is_finite();
+    }
+
     pub fn tick(&mut self) {
         self.remove_hidden_points();
+        //Always expired, so samples from before stats were hidden don't linger once shown again
+        self.stats.remove_before(self.get_start_tick());
         self.shrink_grow();
-        self.draw_axes();
         self.draw_labels();
-        let lines_drawn = self.draw_curve();
+        let overhead = self.draw_axes() + self.draw_stats();
+        let lines_drawn = overhead + self.draw_curve(overhead);
 
         if self.debug {
             let max_possible_lines = current_tick() as i32 - self.get_start_tick() + 1;
-            debug!(
-                "{} lines: {}/{}",
-                self.title, lines_drawn, max_possible_lines
-            );
+            match self.decimation {
+                Decimation::Epsilon => debug!(
+                    "{} lines: {}/{}",
+                    self.title, lines_drawn, max_possible_lines
+                ),
+                Decimation::Budget { max_lines } => debug!(
+                    "{} lines: {}/{} (budget {})",
+                    self.title, lines_drawn, max_possible_lines, max_lines
+                ),
+            }
         }
     }
 
@@ -137,7 +207,7 @@ impl Graph {
         let mut last_front: Option<Datum> = None;
 
         while let Some(front) = self.data.front() {
-            if front.tick >= self.get_start_tick() {
+            if front.tick >= self.get_start_tick() as f64 {
                 break;
             }
             last_front = self.data.pop_front();
@@ -146,43 +216,185 @@ impl Graph {
         //Adjust earliest data point as it leaves the graph for smoother appearance
         if let Some(last_front) = last_front {
             self.data.push_front(last_front);
+            if self.data.len() < 2 {
+                return;
+            }
 
             let t = F64Ex::lerp_inverse(
                 self.get_start_tick() as f64,
-                self.data[0].tick as f64,
-                self.data[1].tick as f64,
+                self.data[0].tick,
+                self.data[1].tick,
             );
 
-            let new_tick = t.lerp(self.data[0].tick as f64, self.data[1].tick as f64);
-            let new_val = t.lerp(self.data[0].value, self.data[1].value);
+            let new_tick = t.lerp(self.data[0].tick, self.data[1].tick);
+            let mut new_val = self.data[0].value;
+            if self.data[0].value.is_finite() && self.data[1].value.is_finite() {
+                new_val = t.lerp(self.data[0].value, self.data[1].value);
+            }
 
             self.data[0] = Datum {
-                tick: new_tick as i32,
+                tick: new_tick,
                 value: new_val,
             };
         }
     }
 
+    //already_drawn is the lines drawn for the rest of the graph this tick. Returns lines drawn.
+    fn draw_curve(&self, already_drawn: i32) -> i32 {
+        return match self.decimation {
+            //Already decimated as it was added, draw it as it is
+            Decimation::Epsilon => self.draw_points(self.data.iter(), i32::MAX),
+            Decimation::Budget { max_lines } => {
+                let curve_lines = (max_lines as i32 - already_drawn).max(0);
+                if curve_lines == 0 {
+                    return 0;
+                }
+                self.draw_points(
+                    self.bucket_min_max(curve_lines as usize).iter(),
+                    curve_lines,
+                )
+            }
+        };
+    }
+
     //Returns lines drawn
-    fn draw_curve(&self) -> i32 {
-        let mut is_first_point = true;
-        let mut last_point: Vec2 = Default::default();
+    fn draw_points<'a>(&self, points: impl Iterator<Item = &'a Datum>, max_lines: i32) -> i32 {
+        let mut last_point: Option<Vec2> = None;
         let mut lines_drawn = 0;
 
-        for pair in self.data.iter().enumerate() {
-            let point = self.get_datum_world_position(&pair.1);
-            if is_first_point == true {
-                is_first_point = false;
-            } else {
+        for datum in points {
+            //Gap
+            if datum.value.is_nan() {
+                last_point = None;
+                continue;
+            }
+
+            let point = self.get_datum_world_position(datum);
+            if let Some(last_point) = last_point {
+                if lines_drawn >= max_lines {
+                    break;
+                }
                 draw_line(last_point, point, self.color);
                 lines_drawn += 1;
             }
-            last_point = point;
+            //Markers are only drawn with whatever budget the curve leaves over
+            if lines_drawn <= max_lines - 3 {
+                lines_drawn += self.draw_clipped_marker(datum, point);
+            }
+            last_point = Some(point);
         }
 
         return lines_drawn;
     }
 
+    //Reduces data to the min and max datum of each time bucket, in time order.
+    //Produces at most max_lines points plus gaps, so at most max_lines - 1 lines.
+    //A gap closes the bucket it falls in, so the curve stays broken either side of it.
+    fn bucket_min_max(&self, max_lines: usize) -> Vec<Datum> {
+        let bucket_count = (max_lines / 2).max(1);
+        let start_tick = self.get_start_tick() as f64;
+        let span = (current_tick() as f64 - start_tick).max(1.0);
+
+        let mut points = Vec::with_capacity(bucket_count * 2);
+        let mut current_bucket = None;
+        //(min index, max index) of the run since the last bucket boundary or gap
+        let mut run: Option<(usize, usize)> = None;
+
+        for (i, datum) in self.data.iter().enumerate() {
+            let bucket_index = (((datum.tick - start_tick) / span * bucket_count as f64).floor()
+                as usize)
+                .min(bucket_count - 1);
+            if current_bucket != Some(bucket_index) {
+                self.push_min_max(&mut points, run.take());
+                current_bucket = Some(bucket_index);
+            }
+
+            if datum.value.is_nan() {
+                self.push_min_max(&mut points, run.take());
+                if points.last().map_or(false, |last| !last.value.is_nan()) {
+                    points.push(Datum {
+                        tick: datum.tick,
+                        value: f64::NAN,
+                    });
+                }
+                continue;
+            }
+
+            run = match run {
+                None => Some((i, i)),
+                Some((min_index, max_index)) => Some((
+                    if self.data[min_index].value <= datum.value {
+                        min_index
+                    } else {
+                        i
+                    },
+                    if self.data[max_index].value >= datum.value {
+                        max_index
+                    } else {
+                        i
+                    },
+                )),
+            };
+        }
+        self.push_min_max(&mut points, run);
+
+        return points;
+    }
+
+    fn push_min_max(&self, points: &mut Vec<Datum>, run: Option<(usize, usize)>) {
+        match run {
+            Some((min_index, max_index)) if min_index == max_index => {
+                points.push(self.data[min_index]);
+            }
+            Some((min_index, max_index)) => {
+                points.push(self.data[min_index.min(max_index)]);
+                points.push(self.data[min_index.max(max_index)]);
+            }
+            None => {}
+        }
+    }
+
+    //Marks values that were clamped to the edge of the graph. Returns lines drawn.
+    fn draw_clipped_marker(&self, datum: &Datum, point: Vec2) -> i32 {
+        const MARKER_SIZE: f64 = 20.0;
+
+        if datum.value.is_infinite() {
+            //Cross for infinities
+            let offset = vec2(MARKER_SIZE, MARKER_SIZE) * 0.5;
+            draw_line(point - offset, point + offset, 0xffffff);
+            draw_line(
+                point + vec2(-offset.x, offset.y),
+                point + vec2(offset.x, -offset.y),
+                0xffffff,
+            );
+            return 2;
+        }
+
+        let direction;
+        if datum.value > self.max {
+            direction = 1.0;
+        } else if datum.value < self.min {
+            direction = -1.0;
+        } else {
+            return 0;
+        }
+
+        //Arrow pointing off the edge the value went past
+        let tip = point + vec2(0.0, MARKER_SIZE * direction);
+        draw_line(point, tip, self.color);
+        draw_line(
+            tip,
+            tip + vec2(-MARKER_SIZE, -MARKER_SIZE * direction) * 0.5,
+            self.color,
+        );
+        draw_line(
+            tip,
+            tip + vec2(MARKER_SIZE, -MARKER_SIZE * direction) * 0.5,
+            self.color,
+        );
+        return 3;
+    }
+
     //Drawing text is quite expensive!
     fn draw_labels(&self) {
         if self.show_labels {
@@ -213,14 +425,66 @@ impl Graph {
         }
     }
 
-    fn draw_axes(&self) {
+    //Returns lines drawn
+    fn draw_stats(&self) -> i32 {
+        if self.stats.count() == 0 {
+            return 0;
+        }
+
+        let mut lines_drawn = 0;
+        if self.show_stat_bands {
+            let mean = self.stats.mean();
+            let std_dev = self.stats.std_dev();
+            lines_drawn += self.draw_horizontal_line(mean, self.color);
+            if std_dev > 0.0 {
+                lines_drawn += self.draw_horizontal_line(mean + std_dev, 0x808080);
+                lines_drawn += self.draw_horizontal_line(mean - std_dev, 0x808080);
+            }
+        }
+
+        if self.show_stats {
+            draw_text!(
+                self.normalised_to_world_pos(vec2(1.0, 1.0)),
+                self.color,
+                "mean: {:.4} sd: {:.4} rms: {:.4} peak: {:.4}",
+                self.stats.mean(),
+                self.stats.std_dev(),
+                self.stats.rms(),
+                self.stats.peak()
+            );
+        }
+        return lines_drawn;
+    }
+
+    //Draws a line across the graph at value. Values outside min/max are not drawn.
+    //Returns lines drawn.
+    fn draw_horizontal_line(&self, value: f64, color: u32) -> i32 {
+        if value < self.min || value > self.max {
+            return 0;
+        }
+        draw_line(
+            self.get_datum_world_position(&Datum {
+                value: value,
+                tick: self.get_start_tick() as f64,
+            }),
+            self.get_datum_world_position(&Datum {
+                value: value,
+                tick: current_tick() as f64,
+            }),
+            color,
+        );
+        return 1;
+    }
+
+    //Returns lines drawn
+    fn draw_axes(&self) -> i32 {
         //Draw axes
         draw_line(
             self.normalised_to_world_pos(vec2(0.0, 0.0)),
             self.normalised_to_world_pos(vec2(0.0, 1.0)),
             0xffffff,
         );
-        
+
         //Draw zero line
         let zero_line_height = f64::from(0.0).clamp(self.min, self.max);
         let mut zero_line_colour = 0xffff00;
@@ -230,14 +494,15 @@ impl Graph {
         draw_line(
             self.get_datum_world_position(&Datum {
                 value: zero_line_height,
-                tick: self.get_start_tick(),
+                tick: self.get_start_tick() as f64,
             }),
             self.get_datum_world_position(&Datum {
                 value: zero_line_height,
-                tick: current_tick() as i32,
+                tick: current_tick() as f64,
             }),
             zero_line_colour,
         );
+        return 2;
     }
 
     fn shrink_grow(&mut self) {
@@ -245,10 +510,18 @@ impl Graph {
         let mut largest = f64::MIN;
         let mut smallest = f64::MAX;
         for datum in &self.data {
+            if !datum.value.is_finite() {
+                continue;
+            }
             largest = largest.max(datum.value);
             smallest = smallest.min(datum.value);
         }
 
+        //Nothing to fit to
+        if largest < smallest {
+            return;
+        }
+
         let mut new_max = self.max;
         let mut new_min = self.min;
 
@@ -275,14 +548,14 @@ impl Graph {
         let start_tick = self.get_start_tick();
         return vec2(
             F64Ex::remap(
-                datum.tick as f64,
+                datum.tick,
                 start_tick as f64,
                 current_tick() as f64,
                 self.position.x,
                 self.position.x + self.size.x,
             ),
             F64Ex::remap(
-                datum.value,
+                datum.value.max(self.min).min(self.max),
                 self.min,
                 self.max,
                 self.position.y,
@@ -292,26 +565,25 @@ impl Graph {
     }
 
     fn normalised_to_world_pos(&self, pos_normalised: Vec2) -> Vec2 {
-        return vec2(
-            F64Ex::lerp(
-                pos_normalised.x,
-                self.position.x,
-                self.position.x + self.size.x,
-            ),
-            F64Ex::lerp(
-                pos_normalised.y,
-                self.position.y,
-                self.position.y + self.size.y,
-            ),
-        );
+        return normalised_to_world_pos(self.position, self.size, pos_normalised);
     }
 
     fn get_start_tick(&self) -> i32 {
         return current_tick() as i32 - (self.timespan / TICK_LENGTH).round() as i32;
     }
+}
 
-    fn point_distance_to_line_squared(p: Vec2, l1: Vec2, l2: Vec2) -> f64 {
-        return ((l2.x - l1.x) * (l1.y - p.y) - (l1.x - p.x) * (l2.y - l1.y)).powf(2.0)
-            / ((l2.x - l1.x).powf(2.0) + (l2.y - l1.y).powf(2.0));
-    }
+//Shared with the other plot types in this module
+#[cfg(feature = "debug-overlay")]
+pub(crate) fn normalised_to_world_pos(position: Vec2, size: Vec2, pos_normalised: Vec2) -> Vec2 {
+    return vec2(
+        F64Ex::lerp(pos_normalised.x, position.x, position.x + size.x),
+        F64Ex::lerp(pos_normalised.y, position.y, position.y + size.y),
+    );
+}
+
+#[cfg(feature = "debug-overlay")]
+pub(crate) fn point_distance_to_line_squared(p: Vec2, l1: Vec2, l2: Vec2) -> f64 {
+    return ((l2.x - l1.x) * (l1.y - p.y) - (l1.x - p.x) * (l2.y - l1.y)).powf(2.0)
+        / ((l2.x - l1.x).powf(2.0) + (l2.y - l1.y).powf(2.0));
 }
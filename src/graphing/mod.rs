@@ -1 +1,3 @@
-pub mod graphing;
\ No newline at end of file
+pub mod graphing;
+pub mod scatter;
+pub mod statistics;
@@ -0,0 +1,343 @@
+use oort_api::prelude::*;
+use std::collections::VecDeque;
+
+#[cfg(feature = "debug-overlay")]
+use super::graphing::{normalised_to_world_pos, point_distance_to_line_squared, F64Ex};
+
+pub struct ScatterDatum {
+    pub tick: i32,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScatterStyle {
+    Lines,  //Connects consecutive points. Use for phase plots / controller trajectories.
+    Points, //Draws a marker per point. Use for scatter of independent samples (eg aim error).
+}
+
+//Plots y against x rather than value against time. Points older than timespan are removed.
+pub struct ScatterGraph {
+    pub position: Vec2, //Position of graph in world space
+    pub size: Vec2,     //Size of graph in world space
+    pub x_min: f64,     //Min x value displayed on graph
+    pub x_max: f64,     //Max x value displayed on graph
+    pub y_min: f64,     //Min y value displayed on graph
+    pub y_max: f64,     //Max y value displayed on graph
+    pub timespan: f64,  //How long points stay on the graph in seconds
+
+    //Same meaning as Graph::epsilon_squared. In Points style this is the minimum squared
+    //world distance between consecutive markers.
+    pub epsilon_squared: f64,
+    pub color: u32,      //Color of curve/markers and labels
+    pub title: String,   //Title string
+    pub x_label: String, //Drawn under the x axis
+    pub y_label: String, //Drawn next to the y axis
+    pub style: ScatterStyle,
+    pub marker_size: f64,  //Size of markers in Points style, world units
+    pub show_labels: bool, //Enable labels. Can be disabled for a few % of ship cpu
+    pub auto_grow: bool,   //Determines whether graph should grow its ranges to accomodate data
+    pub auto_shrink: bool, //Determines whether graph should shrink its ranges to fit data
+    pub debug: bool,       //Debug prints lines drawn/points held
+
+    //Don't set this.
+    pub data: VecDeque<ScatterDatum>,
+}
+
+impl Default for ScatterGraph {
+    fn default() -> Self {
+        Self {
+            position: vec2(-500.0, -500.0),
+            size: vec2(1000.0, 1000.0),
+            x_min: 0.0,
+            x_max: 0.0,
+            y_min: 0.0,
+            y_max: 0.0,
+            timespan: 3.0,
+            epsilon_squared: 100.0,
+            color: 0xff0000,
+            title: String::new(),
+            x_label: String::new(),
+            y_label: String::new(),
+            style: ScatterStyle::Lines,
+            marker_size: 10.0,
+            show_labels: true,
+            auto_grow: true,
+            auto_shrink: true,
+            debug: false,
+            data: VecDeque::new(),
+        }
+    }
+}
+
+impl ScatterGraph {
+    pub fn new() -> ScatterGraph {
+        return Default::default();
+    }
+}
+
+//No-op stand-ins so call sites compile unchanged when the debug-overlay feature is off
+#[cfg(not(feature = "debug-overlay"))]
+impl ScatterGraph {
+    pub fn add(&mut self, _x: f64, _y: f64) {}
+    pub fn tick(&mut self) {}
+}
+
+#[cfg(feature = "debug-overlay")]
+impl ScatterGraph {
+    pub fn add(&mut self, x: f64, y: f64) {
+        if !x.is_finite() || !y.is_finite() {
+            return;
+        }
+
+        let datum = ScatterDatum {
+            tick: current_tick() as i32,
+            x: x,
+            y: y,
+        };
+
+        let datum_world_position = self.get_datum_world_position(&datum);
+        match self.style {
+            ScatterStyle::Lines => {
+                if self.data.len() >= 3 {
+                    let line_start = self.get_datum_world_position(&self.data[self.data.len() - 3]);
+                    let line_end = self.get_datum_world_position(&self.data[self.data.len() - 2]);
+                    let distance_from_line =
+                        point_distance_to_line_squared(datum_world_position, line_start, line_end);
+
+                    //If change from last point is insignificant, move current point rather than adding a new one.
+                    if distance_from_line < self.epsilon_squared {
+                        let last_index = self.data.len() - 1;
+                        self.data[last_index] = datum;
+                        return;
+                    }
+                }
+            }
+            ScatterStyle::Points => {
+                if let Some(last) = self.data.back() {
+                    let last_world_position = self.get_datum_world_position(last);
+                    let delta = datum_world_position - last_world_position;
+                    if delta.dot(delta) < self.epsilon_squared {
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.data.push_back(datum);
+    }
+
+    pub fn tick(&mut self) {
+        self.remove_hidden_points();
+        self.shrink_grow();
+        self.draw_axes();
+        self.draw_labels();
+        let lines_drawn = self.draw_points();
+
+        if self.debug {
+            debug!(
+                "{} lines: {} points: {}",
+                self.title,
+                lines_drawn,
+                self.data.len()
+            );
+        }
+    }
+
+    fn remove_hidden_points(&mut self) {
+        let start_tick = self.get_start_tick();
+        while let Some(front) = self.data.front() {
+            if front.tick >= start_tick {
+                break;
+            }
+            self.data.pop_front();
+        }
+    }
+
+    //Returns lines drawn
+    fn draw_points(&self) -> i32 {
+        let mut lines_drawn = 0;
+
+        match self.style {
+            ScatterStyle::Lines => {
+                let mut last_point: Option<Vec2> = None;
+                for datum in self.data.iter() {
+                    let point = self.get_datum_world_position(datum);
+                    if let Some(last_point) = last_point {
+                        draw_line(last_point, point, self.color);
+                        lines_drawn += 1;
+                    }
+                    last_point = Some(point);
+                }
+
+                //Mark the current state so the direction of travel is visible
+                if let Some(last_point) = last_point {
+                    draw_diamond(last_point, self.marker_size, self.color);
+                    lines_drawn += 4;
+                }
+            }
+            ScatterStyle::Points => {
+                for datum in self.data.iter() {
+                    draw_square(
+                        self.get_datum_world_position(datum),
+                        self.marker_size,
+                        self.color,
+                    );
+                    lines_drawn += 4;
+                }
+            }
+        }
+
+        return lines_drawn;
+    }
+
+    //Drawing text is quite expensive!
+    fn draw_labels(&self) {
+        if !self.show_labels {
+            return;
+        }
+
+        draw_text!(
+            self.normalised_to_world_pos(vec2(0.0, 0.0)),
+            self.color,
+            "{}",
+            self.x_min
+        );
+        draw_text!(
+            self.normalised_to_world_pos(vec2(1.0, 0.0)),
+            self.color,
+            "{}",
+            self.x_max
+        );
+        draw_text!(
+            self.normalised_to_world_pos(vec2(0.0, 1.0)),
+            self.color,
+            "{}",
+            self.y_max
+        );
+
+        if !self.title.is_empty() {
+            draw_text!(
+                self.normalised_to_world_pos(vec2(0.5, 1.0)),
+                self.color,
+                "{}",
+                self.title
+            );
+        }
+
+        if !self.x_label.is_empty() {
+            draw_text!(
+                self.normalised_to_world_pos(vec2(0.5, 0.0)),
+                self.color,
+                "{}",
+                self.x_label
+            );
+        }
+
+        if !self.y_label.is_empty() {
+            draw_text!(
+                self.normalised_to_world_pos(vec2(0.0, 0.5)),
+                self.color,
+                "{}",
+                self.y_label
+            );
+        }
+    }
+
+    fn draw_axes(&self) {
+        //Draw border axes
+        draw_line(
+            self.normalised_to_world_pos(vec2(0.0, 0.0)),
+            self.normalised_to_world_pos(vec2(0.0, 1.0)),
+            0xffffff,
+        );
+        draw_line(
+            self.normalised_to_world_pos(vec2(0.0, 0.0)),
+            self.normalised_to_world_pos(vec2(1.0, 0.0)),
+            0xffffff,
+        );
+
+        //Draw zero lines. Yellow when zero is outside the displayed range.
+        let zero_x = f64::from(0.0).clamp(self.x_min.min(self.x_max), self.x_max.max(self.x_min));
+        let zero_y = f64::from(0.0).clamp(self.y_min.min(self.y_max), self.y_max.max(self.y_min));
+        let mut zero_x_colour = 0xffff00;
+        if 0.0 > self.x_min && 0.0 < self.x_max {
+            zero_x_colour = 0xffffff;
+        }
+        let mut zero_y_colour = 0xffff00;
+        if 0.0 > self.y_min && 0.0 < self.y_max {
+            zero_y_colour = 0xffffff;
+        }
+
+        let zero_x_normalised = zero_x.remap(self.x_min, self.x_max, 0.0, 1.0);
+        let zero_y_normalised = zero_y.remap(self.y_min, self.y_max, 0.0, 1.0);
+        draw_line(
+            self.normalised_to_world_pos(vec2(zero_x_normalised, 0.0)),
+            self.normalised_to_world_pos(vec2(zero_x_normalised, 1.0)),
+            zero_x_colour,
+        );
+        draw_line(
+            self.normalised_to_world_pos(vec2(0.0, zero_y_normalised)),
+            self.normalised_to_world_pos(vec2(1.0, zero_y_normalised)),
+            zero_y_colour,
+        );
+    }
+
+    fn shrink_grow(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let mut x_largest = f64::MIN;
+        let mut x_smallest = f64::MAX;
+        let mut y_largest = f64::MIN;
+        let mut y_smallest = f64::MAX;
+        for datum in &self.data {
+            x_largest = x_largest.max(datum.x);
+            x_smallest = x_smallest.min(datum.x);
+            y_largest = y_largest.max(datum.y);
+            y_smallest = y_smallest.min(datum.y);
+        }
+
+        if self.auto_shrink {
+            self.x_max = self.x_max.min(x_largest);
+            self.x_min = self.x_min.max(x_smallest);
+            self.y_max = self.y_max.min(y_largest);
+            self.y_min = self.y_min.max(y_smallest);
+        }
+
+        if self.auto_grow {
+            self.x_max = self.x_max.max(x_largest);
+            self.x_min = self.x_min.min(x_smallest);
+            self.y_max = self.y_max.max(y_largest);
+            self.y_min = self.y_min.min(y_smallest);
+        }
+    }
+
+    fn get_datum_world_position(&self, datum: &ScatterDatum) -> Vec2 {
+        return vec2(
+            F64Ex::remap(
+                datum.x,
+                self.x_min,
+                self.x_max,
+                self.position.x,
+                self.position.x + self.size.x,
+            ),
+            F64Ex::remap(
+                datum.y,
+                self.y_min,
+                self.y_max,
+                self.position.y,
+                self.position.y + self.size.y,
+            ),
+        );
+    }
+
+    fn normalised_to_world_pos(&self, pos_normalised: Vec2) -> Vec2 {
+        return normalised_to_world_pos(self.position, self.size, pos_normalised);
+    }
+
+    fn get_start_tick(&self) -> i32 {
+        return current_tick() as i32 - (self.timespan / TICK_LENGTH).round() as i32;
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+
+//Running statistics over the raw (undecimated) samples of a series.
+//Samples older than the window are expired by remove_before.
+#[derive(Default)]
+pub struct RollingStats {
+    samples: VecDeque<(i32, f64)>,
+    sum: f64,
+    sum_squared: f64,
+}
+
+impl RollingStats {
+    pub fn new() -> RollingStats {
+        return Default::default();
+    }
+
+    pub fn push(&mut self, tick: i32, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.sum += value;
+        self.sum_squared += value * value;
+        self.samples.push_back((tick, value));
+    }
+
+    pub fn remove_before(&mut self, start_tick: i32) {
+        while let Some(&(tick, value)) = self.samples.front() {
+            if tick >= start_tick {
+                break;
+            }
+            self.sum -= value;
+            self.sum_squared -= value * value;
+            self.samples.pop_front();
+        }
+
+        //Stop floating point drift accumulating over long games
+        if self.samples.is_empty() {
+            self.sum = 0.0;
+            self.sum_squared = 0.0;
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        return self.samples.len();
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        return self.sum / self.samples.len() as f64;
+    }
+
+    //Population standard deviation
+    pub fn std_dev(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let variance = self.sum_squared / self.samples.len() as f64 - mean * mean;
+        return variance.max(0.0).sqrt();
+    }
+
+    pub fn rms(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        return (self.sum_squared / self.samples.len() as f64)
+            .max(0.0)
+            .sqrt();
+    }
+
+    //Largest absolute value in the window
+    pub fn peak(&self) -> f64 {
+        let mut peak: f64 = 0.0;
+        for &(_, value) in &self.samples {
+            peak = peak.max(value.abs());
+        }
+        return peak;
+    }
+}
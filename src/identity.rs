@@ -0,0 +1,199 @@
+use oort_api::prelude::*;
+
+use crate::radio::mux::*;
+use crate::random::Rng;
+
+//Message tags on STREAM_IDENTITY
+const MSG_HELLO: u8 = 0; //[class size, nonce, 0]
+
+//Ticks spent announcing ourselves before the commander is decided
+const DISCOVERY_TICKS: u32 = 30;
+//Chance of sending a hello on any discovery tick. Only one message gets through per tick, so
+//backing off randomly stops the same ship drowning out the others every tick.
+const HELLO_CHANCE: f64 = 0.5;
+//Chance of a hello on any tick after discovery, so ships missed during discovery still get added
+//and late ID collisions are found. Well inside MEMBER_TIMEOUT on average.
+const HEARTBEAT_CHANCE: f64 = 0.05;
+//Members not heard from for this long are presumed dead
+const MEMBER_TIMEOUT: u32 = 120;
+//The commander talks every couple of ticks, so it's given up on sooner
+const COMMANDER_TIMEOUT: u32 = 30;
+
+#[derive(Clone, Copy)]
+pub struct Member {
+    pub id: u16,
+    pub size: u32, //See class_size
+    pub last_heard: u32,
+}
+
+//Gives each ship on the team a unique ID and agrees on a fleet commander over the radio.
+//IDs are random 16 bit numbers. If a hello shows someone else already has ours, whichever of the
+//two has the lower nonce re-rolls and announces the new one straight away.
+//After discovery every ship ranks the members it has heard of, bigger classes first and then by
+//ID, and the top one commands. Hellos carry on at a low rate so everyone ends up with the same
+//members, and so the same ranking, even if some were missed during discovery.
+//Modules built on this report traffic from members with heard(), anyone that goes quiet is
+//dropped and the ranking closes up behind them, so losing the commander promotes the next ship.
+pub struct Identity {
+    pub id: u16,
+    members: Vec<Member>, //Including us, in rank order once discovery is over
+    nonce: f64,           //Tells our own hellos apart from another ship with the same ID
+    announce: bool,       //Send a hello next tick whatever the odds, after re-rolling our ID
+    rng: Rng,
+}
+
+impl Default for Identity {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            members: Vec::new(),
+            nonce: 0.0,
+            announce: false,
+            rng: Rng::default(),
+        }
+    }
+}
+
+//Bigger ships make better commanders, they see more and live longer
+pub fn class_size(class: Class) -> u32 {
+    return match class {
+        Class::Cruiser => 3,
+        Class::Frigate => 2,
+        Class::Fighter => 1,
+        _ => 0,
+    };
+}
+
+impl Identity {
+    pub fn new() -> Identity {
+        //Mix in our spawn position so ships sharing a seed still pick different IDs
+        let salt = position().x.to_bits() ^ position().y.to_bits().rotate_left(32);
+        let mut identity = Identity {
+            rng: Rng::from_game_seed(salt),
+            ..Default::default()
+        };
+        identity.id = identity.roll_id();
+        identity.nonce = identity.rng.next_f64();
+        identity.members.push(Member {
+            id: identity.id,
+            size: class_size(class()),
+            last_heard: current_tick(),
+        });
+        return identity;
+    }
+
+    pub fn is_discovering(&self) -> bool {
+        return current_tick() < DISCOVERY_TICKS;
+    }
+
+    //Our position in the ranking, 0 is the commander. None until discovery is over.
+    pub fn rank(&self) -> Option<usize> {
+        return self.rank_of(self.id);
+    }
+
+    pub fn rank_of(&self, id: u16) -> Option<usize> {
+        if self.is_discovering() {
+            return None;
+        }
+        return self.members.iter().position(|member| member.id == id);
+    }
+
+    pub fn commander(&self) -> Option<u16> {
+        if self.is_discovering() {
+            return None;
+        }
+        return self.members.first().map(|member| member.id);
+    }
+
+    pub fn is_commander(&self) -> bool {
+        return self.commander() == Some(self.id);
+    }
+
+    pub fn is_member(&self, id: u16) -> bool {
+        return self.members.iter().any(|member| member.id == id);
+    }
+
+    pub fn member_count(&self) -> usize {
+        return self.members.len();
+    }
+
+    //Call when a message from a member arrives, so it isn't presumed dead
+    pub fn heard(&mut self, id: u16) {
+        if let Some(member) = self.members.iter_mut().find(|member| member.id == id) {
+            member.last_heard = current_tick();
+        }
+    }
+
+    //Call every tick after the radio and before the modules built on this
+    pub fn tick(&mut self, radio: &mut RadioMux) {
+        if let Some(frame) = radio.received(STREAM_IDENTITY) {
+            if frame.tag == MSG_HELLO && frame.payload[1] != self.nonce {
+                self.handle_hello(frame.sender, frame.payload[0] as u32, frame.payload[1]);
+            }
+        }
+
+        if !self.is_discovering() {
+            self.remove_silent();
+        }
+
+        let chance = if self.is_discovering() {
+            HELLO_CHANCE
+        } else {
+            HEARTBEAT_CHANCE
+        };
+        if self.announce || self.rng.next_f64() < chance {
+            radio.queue(
+                STREAM_IDENTITY,
+                MSG_HELLO,
+                [class_size(class()) as f64, self.nonce, 0.0],
+            );
+            self.announce = false;
+        }
+    }
+
+    fn handle_hello(&mut self, id: u16, size: u32, nonce: f64) {
+        if id == self.id {
+            //Someone else rolled our ID. Only one of us needs to move, the other keeps the ID
+            //and its entry in everyone's members.
+            if nonce > self.nonce {
+                self.members.retain(|member| member.id != id);
+                self.id = self.roll_id();
+                self.members.push(Member {
+                    id: self.id,
+                    size: class_size(class()),
+                    last_heard: current_tick(),
+                });
+                self.announce = true;
+            }
+        }
+
+        if self.is_member(id) {
+            self.heard(id);
+        } else {
+            self.members.push(Member {
+                id: id,
+                size: size,
+                last_heard: current_tick(),
+            });
+        }
+        self.members
+            .sort_by(|a, b| b.size.cmp(&a.size).then(b.id.cmp(&a.id)));
+    }
+
+    fn remove_silent(&mut self) {
+        let commander = self.commander();
+        let id = self.id;
+        self.members.retain(|member| {
+            let timeout = if Some(member.id) == commander {
+                COMMANDER_TIMEOUT
+            } else {
+                MEMBER_TIMEOUT
+            };
+            member.id == id || current_tick() - member.last_heard <= timeout
+        });
+    }
+
+    fn roll_id(&mut self) -> u16 {
+        return self.rng.next_u64() as u16;
+    }
+}
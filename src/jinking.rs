@@ -0,0 +1,67 @@
+use oort_api::prelude::*;
+
+use crate::constants::*;
+use crate::random::Rng;
+
+const RNG_SALT: u64 = 0x6a69_6e6b;
+//Each jink is held for this fraction range of the enemy bullet time of flight. Staying under 1
+//means whatever we were doing when they fired is no longer what we're doing when it arrives.
+const MIN_HOLD_FRACTION: f64 = 0.3;
+const MAX_HOLD_FRACTION: f64 = 0.9;
+const MIN_HOLD_TICKS: u32 = 10;
+//Lateral acceleration as a fraction of max
+const MIN_STRENGTH: f64 = 0.5;
+const MAX_STRENGTH: f64 = 1.0;
+
+//Generates pseudo random lateral acceleration so our path can't be predicted by an enemy
+//leading its shots. Forward acceleration is left to the caller so we keep closing range.
+pub struct Jinking {
+    pub lateral: f64, //Current lateral acceleration as a fraction of max, -1 (right) to 1 (left)
+    hold_until: u32,
+    rng: Rng,
+}
+
+impl Default for Jinking {
+    fn default() -> Self {
+        Self {
+            lateral: 0.0,
+            hold_until: 0,
+            rng: Rng::default(),
+        }
+    }
+}
+
+impl Jinking {
+    pub fn new() -> Jinking {
+        return Jinking {
+            rng: Rng::from_game_seed(RNG_SALT),
+            ..Default::default()
+        };
+    }
+
+    //threat_distance is the distance to the enemy we're avoiding being led by.
+    //Returns the lateral acceleration fraction to fly this tick.
+    pub fn tick(&mut self, threat_distance: f64) -> f64 {
+        if current_tick() < self.hold_until {
+            return self.lateral;
+        }
+
+        let time_of_flight = threat_distance / BULLET_SPEED;
+        let hold_ticks = (self.rng.range(MIN_HOLD_FRACTION, MAX_HOLD_FRACTION) * time_of_flight
+            / TICK_LENGTH) as u32;
+        self.hold_until = current_tick() + hold_ticks.max(MIN_HOLD_TICKS);
+
+        //Always reverse direction, otherwise two jinks the same way are one long predictable one
+        let direction = if self.lateral > 0.0 {
+            -1.0
+        } else if self.lateral < 0.0 {
+            1.0
+        } else if self.rng.next_bool() {
+            1.0
+        } else {
+            -1.0
+        };
+        self.lateral = direction * self.rng.range(MIN_STRENGTH, MAX_STRENGTH);
+        return self.lateral;
+    }
+}
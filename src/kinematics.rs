@@ -125,3 +125,110 @@ pub fn get_optimal_arrive_velocity(distance: f64, max_accel: f64, final_velocity
 //     debug!("optimal vel: {}", optimal_velocity);
 //     return -optimal_velocity / 3.0;
 // }
+
+//Braking in any direction is limited by the weakest thruster, use as get_arrive_accel's max_accel
+pub fn max_braking_acceleration() -> f64 {
+    return max_backward_acceleration().min(max_lateral_acceleration());
+}
+
+//Acceleration that brings a relative position to rest at delta, braking at max_accel.
+//Translational counterpart of the rotation controller in Deflection::track
+pub fn get_arrive_accel(delta: Vec2, relative_velocity: Vec2, max_accel: f64) -> Vec2 {
+    let distance = delta.length();
+    let mut desired_velocity = vec2(0.0, 0.0);
+    if distance > 0.0 {
+        desired_velocity = delta / distance * get_optimal_arrive_velocity(distance, max_accel, 0.0);
+    }
+
+    let mut accel = (desired_velocity - relative_velocity) / TICK_LENGTH;
+    if accel.length() > max_accel {
+        accel = accel.normalize() * max_accel;
+    }
+    return accel;
+}
+
+//Angular acceleration that turns through angle_delta, arriving at target_angular_velocity.
+//Same controller as Deflection::track, for AIs that don't need its graphs
+pub fn get_turn_accel(
+    angle_delta: f64,
+    angular_velocity: f64,
+    target_angular_velocity: f64,
+    max_accel: f64,
+) -> f64 {
+    let desired_velocity =
+        get_optimal_arrive_velocity(angle_delta, max_accel, target_angular_velocity);
+    return ((desired_velocity - angular_velocity) / TICK_LENGTH).clamp(-max_accel, max_accel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_ACCEL: f64 = 30.0;
+    const MAX_ANGULAR_ACCEL: f64 = TAU;
+
+    //Flies get_arrive_accel from rest and checks it arrives and stops
+    fn arrive(target: Vec2, ticks: u32) -> (Vec2, Vec2) {
+        let mut position = vec2(0.0, 0.0);
+        let mut velocity = vec2(0.0, 0.0);
+        for _ in 0..ticks {
+            let accel = get_arrive_accel(target - position, velocity, MAX_ACCEL);
+            assert!(accel.length() <= MAX_ACCEL + 1e-9);
+            velocity += accel * TICK_LENGTH;
+            position += velocity * TICK_LENGTH;
+        }
+        return (position, velocity);
+    }
+
+    #[test]
+    fn arrive_accel_arrives_and_stops() {
+        for target in [vec2(1000.0, 0.0), vec2(-300.0, 2000.0)] {
+            let (position, velocity) = arrive(target, 60 * 60);
+            assert!(
+                (position - target).length() < 1.0,
+                "ended at {:?}",
+                position
+            );
+            assert!(velocity.length() < 1.0, "still moving at {:?}", velocity);
+        }
+    }
+
+    #[test]
+    fn arrive_accel_brakes_when_arriving_too_fast() {
+        //100 m out closing at 200 m/s can't stop in time, so it must be braking flat out
+        let accel = get_arrive_accel(vec2(100.0, 0.0), vec2(200.0, 0.0), MAX_ACCEL);
+        assert!((accel.x + MAX_ACCEL).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arrive_accel_holds_still_on_target() {
+        let accel = get_arrive_accel(vec2(0.0, 0.0), vec2(0.0, 0.0), MAX_ACCEL);
+        assert_eq!(accel.length(), 0.0);
+    }
+
+    //Turning from rest onto a heading off to the side settles on it within a couple of seconds
+    //and stays there, either way round
+    #[test]
+    fn turn_accel_settles_on_heading() {
+        for target_angle in [2.0, -3.0, 0.5] {
+            let mut heading: f64 = 0.0;
+            let mut angular_velocity = 0.0;
+            for tick in 0..600 {
+                let angle_delta = angle_diff(heading, target_angle);
+                if tick >= 120 {
+                    assert!(
+                        angle_delta.abs() < 0.01,
+                        "off by {} at tick {}",
+                        angle_delta,
+                        tick
+                    );
+                }
+
+                let accel = get_turn_accel(angle_delta, angular_velocity, 0.0, MAX_ANGULAR_ACCEL);
+                assert!(accel.abs() <= MAX_ANGULAR_ACCEL);
+                angular_velocity += accel * TICK_LENGTH;
+                heading += angular_velocity * TICK_LENGTH;
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use oort_api::prelude::*;
+
+use crate::shot_ledger::ShotResult;
+use crate::tracking::Track;
+
+const DEFAULT_LEARNING_RATE: f64 = 0.2;
+const DEFAULT_HALF_LIFE: f64 = 3.0; //s
+const DEFAULT_MAX_ACCEL: f64 = 50.0; //m/s^2
+
+//Decay only starts once no shot has resolved for this long
+const DECAY_DELAY: u32 = 60; //ticks
+
+//Shots with less time of flight than this say little about maneuvering and are ignored
+const MIN_TIME_OF_FLIGHT: f64 = 0.1;
+
+//Learns the systematic error of our intercept prediction from resolved shots and shifts
+//future aim points to cancel it. The error is learnt in the target's velocity frame as an
+//unmodelled acceleration, so the correction scales with time of flight squared and follows
+//the target as it turns. Once shots stop resolving the learnt value decays towards zero, so it
+//unlearns stale behaviour instead of fighting the shots that keep it up to date.
+//Every shot in flight was aimed with the same stale correction and reports the same error, so
+//each one only takes on its share of the learning rate. Otherwise the effective gain grows with
+//the fire rate and the correction oscillates.
+pub struct LeadCorrection {
+    pub learning_rate: f64,     //Fraction of each observed error taken on, 0-1
+    pub half_life: f64,         //Seconds for the correction to halve without new shots
+    pub max_accel: f64,         //Clamp on the learnt acceleration, m/s^2
+    pub along_track_accel: f64, //Learnt acceleration along the target's velocity
+    pub cross_track_accel: f64, //Learnt acceleration left of the target's velocity
+    ticks_since_learn: u32,
+}
+
+impl Default for LeadCorrection {
+    fn default() -> Self {
+        Self {
+            learning_rate: DEFAULT_LEARNING_RATE,
+            half_life: DEFAULT_HALF_LIFE,
+            max_accel: DEFAULT_MAX_ACCEL,
+            along_track_accel: 0.0,
+            cross_track_accel: 0.0,
+            ticks_since_learn: 0,
+        }
+    }
+}
+
+impl LeadCorrection {
+    pub fn new() -> LeadCorrection {
+        return Default::default();
+    }
+
+    //Call every tick
+    pub fn tick(&mut self) {
+        self.ticks_since_learn = self.ticks_since_learn.saturating_add(1);
+        if self.ticks_since_learn <= DECAY_DELAY {
+            return;
+        }
+        let decay = f64::powf(0.5, TICK_LENGTH / self.half_life);
+        self.along_track_accel *= decay;
+        self.cross_track_accel *= decay;
+    }
+
+    //in_flight is the number of shots still pending after this one resolved
+    pub fn learn(&mut self, result: &ShotResult, in_flight: usize) {
+        if result.time_of_flight < MIN_TIME_OF_FLIGHT {
+            return;
+        }
+        self.ticks_since_learn = 0;
+
+        //Shots are recorded against the corrected aim point, so the error is what the current
+        //correction still misses by. error = 0.5 * a * t^2
+        let scale = 2.0 / result.time_of_flight.powf(2.0);
+        let gain = self.learning_rate / (in_flight + 1) as f64;
+        self.along_track_accel += result.along_track * scale * gain;
+        self.cross_track_accel += result.cross_track * scale * gain;
+        self.along_track_accel = self
+            .along_track_accel
+            .clamp(-self.max_accel, self.max_accel);
+        self.cross_track_accel = self
+            .cross_track_accel
+            .clamp(-self.max_accel, self.max_accel);
+    }
+
+    //Shifts an intercept predicted with time_of_flight by the learnt error
+    pub fn correct(&self, intercept: Vec2, target: &Track, time_of_flight: f64) -> Vec2 {
+        if target.velocity.length() <= 0.0 {
+            return intercept;
+        }
+
+        let forward = target.velocity.normalize();
+        let left = vec2(-forward.y, forward.x);
+        let displacement = 0.5 * time_of_flight.powf(2.0);
+        return intercept
+            + forward * (self.along_track_accel * displacement)
+            + left * (self.cross_track_accel * displacement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    //Fires every fire_interval ticks at a target holding along_accel the prediction doesn't
+    //model, resolving each shot against the correction it was aimed with. Returns the learnt
+    //along track acceleration each tick.
+    fn fly(along_accel: f64, time_of_flight: u32, fire_interval: u32, ticks: u32) -> Vec<f64> {
+        let mut correction = LeadCorrection::new();
+        let mut pending: VecDeque<(u32, f64)> = VecDeque::new(); //(impact tick, aimed with)
+        let mut history = Vec::new();
+        let tof = time_of_flight as f64 * TICK_LENGTH;
+
+        for tick in 0..ticks {
+            correction.tick();
+            if tick % fire_interval == 0 {
+                pending.push_back((tick + time_of_flight, correction.along_track_accel));
+            }
+            while let Some(&(impact_tick, aimed_with)) = pending.front() {
+                if impact_tick > tick {
+                    break;
+                }
+                pending.pop_front();
+                let error = 0.5 * (along_accel - aimed_with) * tof * tof;
+                let result = ShotResult {
+                    hit: false,
+                    time_of_flight: tof,
+                    miss_distance: error.abs(),
+                    prediction_error: vec2(error, 0.0),
+                    along_track: error,
+                    cross_track: 0.0,
+                };
+                correction.learn(&result, pending.len());
+            }
+            history.push(correction.along_track_accel);
+        }
+        return history;
+    }
+
+    #[test]
+    fn converges_on_constant_accel_without_overshoot() {
+        //Fast and slow guns, short and long range
+        for (accel, time_of_flight, fire_interval) in
+            [(20.0, 90, 4), (-30.0, 60, 2), (20.0, 120, 8)]
+        {
+            let history = fly(accel, time_of_flight, fire_interval, 60 * 30);
+            let last = *history.last().unwrap();
+            assert!(
+                (last - accel).abs() < 0.05 * accel.abs(),
+                "learnt {} for {}",
+                last,
+                accel
+            );
+            for learnt in history {
+                assert!(learnt.abs() <= accel.abs() * 1.01, "overshot to {}", learnt);
+            }
+        }
+    }
+
+    #[test]
+    fn decays_once_shots_stop() {
+        let mut correction = LeadCorrection::new();
+        correction.along_track_accel = 10.0;
+        for _ in 0..DECAY_DELAY {
+            correction.tick();
+        }
+        assert_eq!(correction.along_track_accel, 10.0);
+        for _ in 0..(DEFAULT_HALF_LIFE / TICK_LENGTH).round() as u32 {
+            correction.tick();
+        }
+        assert!((correction.along_track_accel - 5.0).abs() < 0.1);
+    }
+}
@@ -1,13 +1,43 @@
+//Same as oort_api's debug! but compiled out without the debug-overlay feature.
+//Must stay above the mod declarations so the modules can see it.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "debug-overlay") {
+            debug!($($arg)*);
+        }
+    };
+}
+
+pub mod abilities;
 pub mod ais;
+pub mod class_info;
 pub mod constants;
+pub mod debug_draw;
+pub mod evasion;
 pub mod f64_extensions;
+pub mod fire_control;
+pub mod formation;
 pub mod graphing;
+pub mod identity;
+pub mod jinking;
 pub mod kinematics;
+pub mod lead_correction;
+pub mod maneuver_envelope;
+pub mod point_defense;
+pub mod radar;
+pub mod radio;
+pub mod random;
+pub mod shot_ledger;
+pub mod spread_fire;
+pub mod target_allocation;
+pub mod threat_assessment;
+pub mod tracking;
+pub mod trajectory;
 pub mod vec_extensions;
 
 // use crate::ais::ai::*;
 use ais::ai::AI;
-use ais::ai_selector::get_class_ai;
+use ais::ai_selector::{get_class_ai, get_scenario_ai};
 use oort_api::prelude::*;
 
 pub struct Ship {
@@ -16,9 +46,10 @@ pub struct Ship {
 
 impl Ship {
     pub fn new() -> Ship {
-        let ai = get_class_ai(class());
-        debug!("Scenario: {}", scenario_name());
-        debug!("Active AI: {}", ai.name());
+        let ai =
+            get_scenario_ai(scenario_name(), class()).unwrap_or_else(|| get_class_ai(class()));
+        debug_log!("Scenario: {}", scenario_name());
+        debug_log!("Active AI: {}", ai.name());
         return Ship { ai: ai };
     }
 
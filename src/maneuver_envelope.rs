@@ -0,0 +1,107 @@
+use oort_api::prelude::*;
+
+use crate::class_info::ClassInfo;
+use crate::debug_draw::{self, DebugCategory};
+use crate::kinematics::*;
+use crate::tracking::Track;
+
+//Positions a contact could reach by a given time. The boundary is sampled by holding max
+//acceleration in evenly spaced directions, which for constant thrust traces the edge of the
+//reachable region.
+pub struct ManeuverEnvelope {
+    pub ticks: i32,
+    pub centre: Vec2,        //World position with no acceleration at all
+    pub boundary: Vec<Vec2>, //World positions, counter clockwise, not closed
+}
+
+impl ManeuverEnvelope {
+    //Largest distance from the centre to the boundary
+    pub fn radius(&self) -> f64 {
+        let mut radius: f64 = 0.0;
+        for point in &self.boundary {
+            radius = radius.max((*point - self.centre).length());
+        }
+        return radius;
+    }
+
+    //Whether a world position can be reached by the envelope's time
+    pub fn contains(&self, point: Vec2) -> bool {
+        //Even-odd ray cast
+        let mut inside = false;
+        let count = self.boundary.len();
+        for i in 0..count {
+            let a = self.boundary[i];
+            let b = self.boundary[(i + 1) % count];
+            if (a.y > point.y) != (b.y > point.y) {
+                let crossing_x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < crossing_x {
+                    inside = !inside;
+                }
+            }
+        }
+        return inside;
+    }
+
+    pub fn draw(&self, color: u32) {
+        if !debug_draw::is_category_enabled(DebugCategory::Prediction) || self.boundary.is_empty() {
+            return;
+        }
+
+        let mut closed = self.boundary.clone();
+        closed.push(self.boundary[0]);
+        debug_draw::polyline(DebugCategory::Prediction, &closed, color);
+        debug_draw::diamond(DebugCategory::Prediction, self.centre, 10.0, color);
+    }
+}
+
+//Max acceleration at angle from forward, using the class's forward/backward limits along
+//forward and lateral limit across it
+pub fn max_maneuver_accel(info: &ClassInfo, forward: Vec2, angle: f64) -> Vec2 {
+    let left = vec2(-forward.y, forward.x);
+    let along = angle.cos();
+    let across = angle.sin();
+    let along_limit = if along >= 0.0 {
+        info.max_forward_accel
+    } else {
+        info.max_backward_accel
+    };
+    return forward * (along * along_limit) + left * (across * info.max_lateral_accel);
+}
+
+//Contacts don't report their heading, so their velocity is taken as forward
+pub fn track_forward(track: &Track) -> Vec2 {
+    if track.velocity.length() > 0.0 {
+        return track.velocity.normalize();
+    }
+    return vec2(1.0, 0.0);
+}
+
+//Envelope ticks from now. Built from the last observation, so a stale track gets a bigger one.
+pub fn reachable_envelope(
+    track: &Track,
+    info: &ClassInfo,
+    ticks: i32,
+    directions: usize,
+) -> ManeuverEnvelope {
+    let forward = track_forward(track);
+    //The contact could have been maneuvering since it was last seen too
+    let elapsed = ticks + track.age() as i32;
+    let offset = |accel: Vec2| -> Vec2 {
+        vec2(
+            delta_distance_iterative(elapsed, track.velocity.x, accel.x, 0.0),
+            delta_distance_iterative(elapsed, track.velocity.y, accel.y, 0.0),
+        )
+    };
+
+    let mut boundary = Vec::with_capacity(directions);
+    for i in 0..directions {
+        let angle = i as f64 / directions as f64 * TAU;
+        boundary.push(track.position + offset(max_maneuver_accel(info, forward, angle)));
+    }
+
+    return ManeuverEnvelope {
+        ticks: ticks,
+        centre: track.position + offset(vec2(0.0, 0.0)),
+        boundary: boundary,
+    };
+}
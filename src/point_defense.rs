@@ -0,0 +1,108 @@
+use oort_api::prelude::*;
+
+use crate::class_info::{class_info, class_turrets, TurretSpec};
+use crate::kinematics::*;
+use crate::threat_assessment::TargetScore;
+use crate::tracking::{Track, TrackTable};
+
+//Don't bother engaging threats whose intercept is further out than this
+const MAX_TIME_OF_FLIGHT: f64 = 2.0; //s
+
+//Shoots down missiles and torpedoes closing on us with the ship's turrets. Threats are ranked
+//by time to impact and each turret takes the most imminent threat it can reach that no other
+//turret has, so spare turrets double up on the worst threat. Independent of the anti-ship fire
+//control.
+#[derive(Default)]
+pub struct PointDefense {
+    pub turrets: Vec<TurretSpec>,
+    pub assignments: Vec<Option<u32>>, //Track id each turret is engaging, same order as turrets
+}
+
+impl PointDefense {
+    pub fn new(class: Class) -> PointDefense {
+        let turrets = class_turrets(class);
+        return PointDefense {
+            assignments: vec![None; turrets.len()],
+            turrets: turrets,
+        };
+    }
+
+    //Whether weapon index is busy with point defense this tick
+    pub fn is_engaging(&self, index: usize) -> bool {
+        return self
+            .turrets
+            .iter()
+            .zip(&self.assignments)
+            .any(|(turret, assignment)| turret.index == index && assignment.is_some());
+    }
+
+    //Call every tick after the contacts have been updated, with their prioritize scores
+    pub fn tick(&mut self, contacts: &TrackTable, priorities: &[TargetScore]) {
+        let threats = imminent_threats(contacts, priorities);
+
+        for (i, turret) in self.turrets.iter().enumerate() {
+            //Only threats this turret's bullets can reach in time, most imminent first
+            let reachable: Vec<(&Track, Vec2)> = threats
+                .iter()
+                .map(|track| (*track, threat_intercept(track, turret.bullet_speed)))
+                .filter(|(_, intercept)| {
+                    intercept.length() / turret.bullet_speed <= MAX_TIME_OF_FLIGHT
+                })
+                .collect();
+
+            //The most imminent one nobody has taken yet, otherwise double up on the worst
+            let assigned = &self.assignments[..i];
+            let choice = reachable
+                .iter()
+                .find(|(track, _)| !assigned.contains(&Some(track.id)))
+                .or(reachable.first());
+
+            self.assignments[i] = None;
+            if let Some((track, intercept)) = choice {
+                self.assignments[i] = Some(track.id);
+                aim(turret.index, intercept.angle());
+                if reload_ticks(turret.index) == 0 {
+                    fire(turret.index);
+                }
+            }
+        }
+    }
+}
+
+//Missiles and torpedoes from the prioritized contacts that are closing on us, most imminent first
+pub fn imminent_threats<'a>(
+    contacts: &'a TrackTable,
+    priorities: &[TargetScore],
+) -> Vec<&'a Track> {
+    let mut threats: Vec<(&Track, f64)> = priorities
+        .iter()
+        .filter(|score| score.time_to_impact.is_finite())
+        .filter_map(|score| {
+            contacts
+                .get(score.id)
+                .map(|track| (track, score.time_to_impact))
+        })
+        .filter(|(track, _)| track.class == Class::Missile || track.class == Class::Torpedo)
+        .collect();
+    threats.sort_by(|a, b| a.1.total_cmp(&b.1));
+    return threats.into_iter().map(|(track, _)| track).collect();
+}
+
+//Intercept relative to us, same frame as predict_intercept. Homing weapons are assumed to burn
+//at full thrust straight at us unless the track shows them pulling harder than that.
+pub fn threat_intercept(track: &Track, bullet_speed: f64) -> Vec2 {
+    let target_delta = track.current_position() - position();
+    let homing_accel = -target_delta.normalize() * class_info(track.class).max_forward_accel;
+    let mut accel = homing_accel;
+    if track.accel.length() > homing_accel.length() {
+        accel = track.accel;
+    }
+
+    return predict_intercept(
+        target_delta,
+        track.velocity - velocity(),
+        accel,
+        vec2(0.0, 0.0),
+        bullet_speed,
+    );
+}
@@ -0,0 +1,59 @@
+use oort_api::prelude::*;
+
+use crate::class_info::class_info;
+use crate::tracking::Track;
+
+//Narrowest beam we'll use, however certain a track is
+const MIN_BEAM_WIDTH: f64 = TAU / 720.0;
+//Added to the uncertainty so measurement noise doesn't put the contact just outside the gate
+const BASE_MARGIN: f64 = 20.0; //m
+
+//Each missed look doubles the gate, up to this many times
+const MAX_WIDEN_STEPS: u32 = 4;
+
+#[derive(Clone, Copy)]
+pub struct BeamGate {
+    pub heading: f64,
+    pub width: f64,
+    pub min_distance: f64,
+    pub max_distance: f64,
+}
+
+impl BeamGate {
+    pub fn apply(&self) {
+        set_radar_heading(self.heading);
+        set_radar_width(self.width);
+        set_radar_min_distance(self.min_distance);
+        set_radar_max_distance(self.max_distance);
+    }
+}
+
+//Tightest beam and distance gates that still cover the region a track could be in. Radar
+//detection range grows as the beam narrows, and the distance gates keep other contacts out.
+//uncertainty is how far the contact could be from its extrapolated position (m). misses is how
+//many looks in a row failed to find it, which widens the gate in case the estimate is off.
+pub fn track_gate(track: &Track, uncertainty: f64, misses: u32) -> BeamGate {
+    let widen = f64::powf(2.0, misses.min(MAX_WIDEN_STEPS) as f64);
+    let radius = (uncertainty + class_info(track.class).radius + BASE_MARGIN) * widen;
+    return gate_around(track.current_position(), radius);
+}
+
+//Tightest beam and distance gates covering a circle around a world position
+pub fn gate_around(point: Vec2, radius: f64) -> BeamGate {
+    let delta = point - position();
+    let distance = delta.length();
+
+    //Half angle the uncertainty circle subtends from us. If we're inside it, look everywhere.
+    let width = if radius >= distance {
+        TAU
+    } else {
+        (2.0 * (radius / distance).asin()).max(MIN_BEAM_WIDTH)
+    };
+
+    return BeamGate {
+        heading: delta.angle(),
+        width: width,
+        min_distance: (distance - radius).max(0.0),
+        max_distance: distance + radius,
+    };
+}
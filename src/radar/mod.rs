@@ -0,0 +1,3 @@
+pub mod gating;
+pub mod reacquisition;
+pub mod scheduler;
@@ -0,0 +1,97 @@
+use oort_api::prelude::*;
+
+use super::gating::{gate_around, BeamGate};
+use super::scheduler::track_uncertainty;
+use crate::class_info::class_info;
+use crate::kinematics::*;
+use crate::tracking::Track;
+
+//Consecutive missed looks before a track is considered lost
+const LOST_MISSES: u32 = 2;
+//Give up looking where the target should be and go back to a full search after this long
+const DEFAULT_TIMEOUT: u32 = 90; //ticks
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReacquisitionState {
+    Tracking,
+    Reacquiring { lost_tick: u32 }, //Scanning the cone the target could have reached
+    Searching,                      //Timed out, searching with its share of looks until seen again
+}
+
+//Keeps hold of one important track (eg our target) when it drops out of the scan. The last
+//known state is projected forward with the same kinematics as predict_intercept and the beam
+//scans the region the target could have reached, which grows with time since it was last
+//seen. After the timeout we fall back to a full search.
+pub struct Reacquisition {
+    pub timeout: u32,
+    pub state: ReacquisitionState,
+}
+
+impl Default for Reacquisition {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            state: ReacquisitionState::Tracking,
+        }
+    }
+}
+
+impl Reacquisition {
+    pub fn new() -> Reacquisition {
+        return Default::default();
+    }
+
+    //target is the track we're holding, None if it has been forgotten.
+    //misses is consecutive missed looks at it.
+    pub fn update(&mut self, target: Option<&Track>, misses: u32) {
+        let target = match target {
+            Some(target) => target,
+            None => {
+                if self.state != ReacquisitionState::Tracking {
+                    self.state = ReacquisitionState::Searching;
+                }
+                return;
+            }
+        };
+
+        //Seen again
+        if target.age() == 0 {
+            self.state = ReacquisitionState::Tracking;
+            return;
+        }
+
+        self.state = match self.state {
+            ReacquisitionState::Tracking if misses >= LOST_MISSES => {
+                ReacquisitionState::Reacquiring {
+                    lost_tick: target.last_update_tick,
+                }
+            }
+            ReacquisitionState::Reacquiring { lost_tick }
+                if current_tick() - lost_tick > self.timeout =>
+            {
+                ReacquisitionState::Searching
+            }
+            state => state,
+        };
+    }
+
+    pub fn is_lost(&self) -> bool {
+        return self.state != ReacquisitionState::Tracking;
+    }
+
+    //Beam covering everywhere the target could be now
+    pub fn gate(&self, target: &Track) -> BeamGate {
+        let radius = track_uncertainty(target) + class_info(target.class).radius;
+        return gate_around(project(target), radius);
+    }
+}
+
+//Last known state projected to now, including jerk
+pub fn project(track: &Track) -> Vec2 {
+    let age = track.age() as f64 * TICK_LENGTH;
+    return track.position
+        + vec2(
+            delta_distance(age, track.velocity.x, track.accel.x, track.jerk.x),
+            delta_distance(age, track.velocity.y, track.accel.y, track.jerk.y),
+        );
+}
@@ -0,0 +1,181 @@
+use oort_api::prelude::*;
+use std::collections::HashMap;
+
+use super::gating::track_gate;
+use super::reacquisition::*;
+use crate::class_info::class_info;
+use crate::tracking::{Track, TrackTable};
+
+const DEFAULT_SEARCH_WIDTH: f64 = TAU / 16.0;
+//Revisit a track once its position could have drifted this far from our estimate
+const DEFAULT_REVISIT_UNCERTAINTY: f64 = 50.0; //m
+
+//At least one tick in this many is spent searching, however many tracks are due
+const DEFAULT_SEARCH_EVERY: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RadarTask {
+    Search,
+    Track { id: u32 },
+}
+
+//Shares one radar between sweeping for new contacts and revisiting known ones. Each tick the
+//scan result from last tick's beam goes into the TrackTable, then the beam is pointed for the
+//next tick: at the track whose position has become most uncertain if any are due a revisit,
+//otherwise on round the search sweep. Track looks use the tightest beam covering the track's
+//uncertainty (see gating), widening after each look that misses. If the priority track is
+//lost, track looks go to reacquiring it until it's found or reacquisition times out. After that
+//it gets its share of track looks as extra searching, the rest keep revisiting the others.
+pub struct RadarScheduler {
+    pub search_width: f64,
+    pub revisit_uncertainty: f64,
+    pub search_every: u32,
+    pub task: RadarTask, //What the beam is pointed at for this tick's scan
+    pub last_task_found: bool,
+    search_heading: f64,
+    ticks_since_search: u32,
+    track_looks: u32,          //Free looks so far, for sharing while searching
+    misses: HashMap<u32, u32>, //Consecutive track looks that found nothing, by track id
+    priority: Option<u32>,     //Track to reacquire if lost, see set_priority
+    pub reacquisition: Reacquisition,
+}
+
+impl Default for RadarScheduler {
+    fn default() -> Self {
+        Self {
+            search_width: DEFAULT_SEARCH_WIDTH,
+            revisit_uncertainty: DEFAULT_REVISIT_UNCERTAINTY,
+            search_every: DEFAULT_SEARCH_EVERY,
+            task: RadarTask::Search,
+            last_task_found: false,
+            search_heading: 0.0,
+            ticks_since_search: 0,
+            track_looks: 0,
+            misses: HashMap::new(),
+            priority: None,
+            reacquisition: Reacquisition::new(),
+        }
+    }
+}
+
+//How far a track could be from its extrapolated position now, m
+pub fn track_uncertainty(track: &Track) -> f64 {
+    let age = track.age() as f64 * TICK_LENGTH;
+    let accel = class_info(track.class).max_accel().max(track.accel_error);
+    return 0.5 * accel * age * age;
+}
+
+impl RadarScheduler {
+    pub fn new() -> RadarScheduler {
+        return Default::default();
+    }
+
+    //Call every tick before anything reads the TrackTable
+    pub fn tick(&mut self, contacts: &mut TrackTable) {
+        self.last_task_found = false;
+        if let Some(contact) = scan() {
+            let id = contacts.observe(contact.class, contact.position, contact.velocity);
+            self.last_task_found = match self.task {
+                RadarTask::Search => true,
+                RadarTask::Track { id: task_id } => task_id == id,
+            };
+        }
+
+        if let RadarTask::Track { id } = self.task {
+            if self.last_task_found {
+                self.misses.remove(&id);
+            } else {
+                *self.misses.entry(id).or_insert(0) += 1;
+            }
+        }
+        self.misses.retain(|id, _| contacts.get(*id).is_some());
+
+        if let Some(id) = self.priority {
+            self.reacquisition.update(contacts.get(id), self.misses(id));
+        }
+
+        self.task = self.next_task(contacts);
+        match self.task {
+            RadarTask::Search => self.point_search(),
+            RadarTask::Track { id } => match contacts.get(id) {
+                Some(track) => self.point_track(track),
+                None => self.point_search(),
+            },
+        }
+    }
+
+    //The track we most care about keeping, eg our current target
+    pub fn set_priority(&mut self, id: Option<u32>) {
+        if self.priority != id {
+            self.priority = id;
+            self.reacquisition = Reacquisition::new();
+        }
+    }
+
+    fn next_task(&mut self, contacts: &TrackTable) -> RadarTask {
+        if self.ticks_since_search + 1 >= self.search_every {
+            return RadarTask::Search;
+        }
+
+        match (self.reacquisition.state, self.priority) {
+            (ReacquisitionState::Reacquiring { .. }, Some(id)) => {
+                return RadarTask::Track { id: id }
+            }
+            _ => {}
+        }
+
+        let mut most_uncertain: Option<(u32, f64)> = None;
+        let mut due = 0;
+        for track in &contacts.tracks {
+            let uncertainty = track_uncertainty(track);
+            if uncertainty < self.revisit_uncertainty {
+                continue;
+            }
+            due += 1;
+            match most_uncertain {
+                Some((_, best)) if best >= uncertainty => {}
+                _ => most_uncertain = Some((track.id, uncertainty)),
+            }
+        }
+
+        //The lost track competes with those due a revisit for one look in due + 1
+        self.track_looks = self.track_looks.wrapping_add(1);
+        if self.reacquisition.state == ReacquisitionState::Searching
+            && self.track_looks % (due + 1) == 0
+        {
+            return RadarTask::Search;
+        }
+
+        return match most_uncertain {
+            Some((id, _)) => RadarTask::Track { id: id },
+            None => RadarTask::Search,
+        };
+    }
+
+    fn point_search(&mut self) {
+        self.search_heading += self.search_width;
+        self.ticks_since_search = 0;
+        set_radar_heading(self.search_heading);
+        set_radar_width(self.search_width);
+        set_radar_min_distance(0.0);
+        set_radar_max_distance(f64::MAX);
+    }
+
+    fn point_track(&mut self, track: &Track) {
+        self.ticks_since_search += 1;
+        let reacquiring = matches!(
+            self.reacquisition.state,
+            ReacquisitionState::Reacquiring { .. }
+        );
+        if reacquiring && self.priority == Some(track.id) {
+            self.reacquisition.gate(track).apply();
+        } else {
+            track_gate(track, track_uncertainty(track), self.misses(track.id)).apply();
+        }
+    }
+
+    //Consecutive looks at a track that didn't find it
+    pub fn misses(&self, id: u32) -> u32 {
+        return self.misses.get(&id).copied().unwrap_or(0);
+    }
+}
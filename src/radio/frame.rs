@@ -0,0 +1,223 @@
+//Packs a typed, signed message into the four f64s an Oort radio carries. The first f64 is a
+//header holding an integer below 2^52, so it survives the trip exactly:
+//
+//  bits 48-51  tag       message type within the stream
+//  bits 45-47  stream    logical stream, see RadioMux
+//  bits 29-44  sender    Identity ID of the sending ship
+//  bits 21-28  sequence  per sender and stream, wraps
+//  bits  0-20  checksum  keyed hash of everything else
+//
+//The other three f64s are payload. The checksum is keyed, so other AIs' transmissions on a
+//shared channel and corrupted frames are rejected. It isn't authentication: it isn't
+//cryptographic, and it's only as private as the key (see TEAM_KEY).
+
+pub const TAG_BITS: u32 = 4;
+pub const STREAM_BITS: u32 = 3;
+const SENDER_BITS: u32 = 16;
+const SEQUENCE_BITS: u32 = 8;
+const CHECKSUM_BITS: u32 = 21;
+
+const CHECKSUM_SHIFT: u32 = 0;
+const SEQUENCE_SHIFT: u32 = CHECKSUM_SHIFT + CHECKSUM_BITS;
+const SENDER_SHIFT: u32 = SEQUENCE_SHIFT + SEQUENCE_BITS;
+const STREAM_SHIFT: u32 = SENDER_SHIFT + SENDER_BITS;
+const TAG_SHIFT: u32 = STREAM_SHIFT + STREAM_BITS;
+const HEADER_BITS: u32 = TAG_SHIFT + TAG_BITS;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frame {
+    pub tag: u8,
+    pub stream: u8,
+    pub sender: u16,
+    pub sequence: u8,
+    pub payload: [f64; 3],
+}
+
+fn mask(bits: u32) -> u64 {
+    return (1u64 << bits) - 1;
+}
+
+//splitmix64 finaliser
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    return x ^ (x >> 31);
+}
+
+//fields is the header with the checksum bits clear
+fn checksum(fields: u64, payload: &[f64; 3], key: u64) -> u64 {
+    let mut hash = mix(key ^ fields);
+    for value in payload {
+        hash = mix(hash ^ value.to_bits());
+    }
+    return hash & mask(CHECKSUM_BITS);
+}
+
+//Tag and stream are truncated to their field widths
+pub fn encode(frame: &Frame, key: u64) -> [f64; 4] {
+    let fields = (frame.tag as u64 & mask(TAG_BITS)) << TAG_SHIFT
+        | (frame.stream as u64 & mask(STREAM_BITS)) << STREAM_SHIFT
+        | (frame.sender as u64) << SENDER_SHIFT
+        | (frame.sequence as u64) << SEQUENCE_SHIFT;
+    let header = fields | checksum(fields, &frame.payload, key) << CHECKSUM_SHIFT;
+    return [
+        header as f64,
+        frame.payload[0],
+        frame.payload[1],
+        frame.payload[2],
+    ];
+}
+
+//None if the header isn't one of ours or the checksum doesn't match
+pub fn decode(message: [f64; 4], key: u64) -> Option<Frame> {
+    let header = message[0];
+    if !(header >= 0.0 && header < (1u64 << HEADER_BITS) as f64) || header.fract() != 0.0 {
+        return None;
+    }
+    let header = header as u64;
+    let payload = [message[1], message[2], message[3]];
+
+    let fields = header & !(mask(CHECKSUM_BITS) << CHECKSUM_SHIFT);
+    if (header >> CHECKSUM_SHIFT) & mask(CHECKSUM_BITS) != checksum(fields, &payload, key) {
+        return None;
+    }
+
+    return Some(Frame {
+        tag: ((header >> TAG_SHIFT) & mask(TAG_BITS)) as u8,
+        stream: ((header >> STREAM_SHIFT) & mask(STREAM_BITS)) as u8,
+        sender: ((header >> SENDER_SHIFT) & mask(SENDER_BITS)) as u16,
+        sequence: ((header >> SEQUENCE_SHIFT) & mask(SEQUENCE_BITS)) as u8,
+        payload: payload,
+    });
+}
+
+//Whether sequence comes after last, allowing for wrap around
+pub fn is_newer(sequence: u8, last: u8) -> bool {
+    let ahead = sequence.wrapping_sub(last);
+    return ahead != 0 && ahead < 128;
+}
+
+//Whether to accept sequence from a sender and stream last heard with last_sequence at last_tick.
+//After stale_ticks of silence the sender may have sent half the sequence space or more, so
+//is_newer can't be trusted and anything is accepted.
+pub fn is_fresh(
+    sequence: u8,
+    last_sequence: u8,
+    last_tick: u32,
+    tick: u32,
+    stale_ticks: u32,
+) -> bool {
+    return tick.saturating_sub(last_tick) >= stale_ticks || is_newer(sequence, last_sequence);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: u64 = 0x1234_5678_9abc_def0;
+
+    fn frame() -> Frame {
+        return Frame {
+            tag: 3,
+            stream: 2,
+            sender: 0xbeef,
+            sequence: 42,
+            payload: [1234.5, -6789.25, 0.125],
+        };
+    }
+
+    #[test]
+    fn round_trip() {
+        let frame = frame();
+        assert_eq!(decode(encode(&frame, KEY), KEY), Some(frame));
+    }
+
+    #[test]
+    fn round_trip_field_limits() {
+        for frame in [
+            Frame {
+                tag: 0,
+                stream: 0,
+                sender: 0,
+                sequence: 0,
+                payload: [0.0, -0.0, f64::MIN_POSITIVE],
+            },
+            Frame {
+                tag: 15,
+                stream: 7,
+                sender: u16::MAX,
+                sequence: u8::MAX,
+                payload: [f64::MAX, f64::MIN, f64::INFINITY],
+            },
+        ] {
+            assert_eq!(decode(encode(&frame, KEY), KEY), Some(frame));
+        }
+    }
+
+    #[test]
+    fn header_is_exact_integer() {
+        let header = encode(&frame(), KEY)[0];
+        assert_eq!(header.fract(), 0.0);
+        assert!(header < (1u64 << 53) as f64);
+    }
+
+    #[test]
+    fn oversized_fields_truncated() {
+        let mut frame = frame();
+        frame.tag = 0xff;
+        frame.stream = 0xff;
+        let decoded = decode(encode(&frame, KEY), KEY).unwrap();
+        assert_eq!(decoded.tag, 15);
+        assert_eq!(decoded.stream, 7);
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        assert_eq!(decode(encode(&frame(), KEY), KEY ^ 1), None);
+    }
+
+    #[test]
+    fn rejects_tampered_payload() {
+        let mut message = encode(&frame(), KEY);
+        message[2] += 1.0;
+        assert_eq!(decode(message, KEY), None);
+    }
+
+    #[test]
+    fn rejects_tampered_header() {
+        let mut message = encode(&frame(), KEY);
+        //Bump the sender
+        message[0] += (1u64 << SENDER_SHIFT) as f64;
+        assert_eq!(decode(message, KEY), None);
+    }
+
+    #[test]
+    fn rejects_malformed_header() {
+        for header in [f64::NAN, -1.0, 0.5, (1u64 << 52) as f64, f64::INFINITY] {
+            assert_eq!(decode([header, 0.0, 0.0, 0.0], KEY), None);
+        }
+    }
+
+    #[test]
+    fn sequence_wraps() {
+        assert!(is_newer(1, 0));
+        assert!(is_newer(0, 255));
+        assert!(is_newer(10, 250));
+        assert!(!is_newer(0, 0));
+        assert!(!is_newer(250, 10));
+        assert!(!is_newer(0, 1));
+    }
+
+    #[test]
+    fn stale_sender_resynchronises() {
+        //128 frames missed puts a sender's sequence behind ours until it comes back round
+        assert!(!is_newer(10, 200));
+        assert!(!is_fresh(10, 200, 100, 150, 60));
+        assert!(is_fresh(10, 200, 100, 160, 60));
+        //Replays within the window are still rejected
+        assert!(!is_fresh(200, 200, 100, 101, 60));
+        assert!(is_fresh(201, 200, 100, 101, 60));
+    }
+}
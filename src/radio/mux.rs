@@ -0,0 +1,140 @@
+use oort_api::prelude::*;
+use std::collections::HashMap;
+
+use super::frame::*;
+
+//Key for frame checksums. This default is public, so it only keeps out crosstalk from other
+//AIs and corrupted frames: anyone who reads the source, or a mirror match, can forge frames.
+//Build with OORT_TEAM_KEY set (decimal or 0x hex) and build.rs bakes a private key into the
+//bundle instead.
+pub const TEAM_KEY: u64 = 0x6f6f_7274_5f61_6921;
+pub const DEFAULT_CHANNEL: usize = 2;
+
+//Logical streams, one per module talking on the radio
+pub const STREAM_IDENTITY: u8 = 0;
+pub const STREAM_FORMATION: u8 = 1;
+pub const STREAM_ALLOCATION: u8 = 2;
+const STREAM_COUNT: usize = 1 << STREAM_BITS;
+//Sequence numbers are only 8 bits, so once we've missed 128 frames from a sender its new ones
+//look like replays. That takes at least 128 ticks of not hearing it, so after this long its
+//next frame is taken as the new starting point instead.
+const SEQUENCE_TIMEOUT: u32 = 60; //ticks
+
+#[derive(Clone, Copy)]
+struct Outgoing {
+    tag: u8,
+    payload: [f64; 3],
+}
+
+//Carries several logical streams over one radio channel. Each tick the frame received is
+//checked and made available to its stream, and modules queue at most one message per stream.
+//Only one message can go out per tick, so flush takes turns between the streams with something
+//queued and drops the rest, they're rebuilt from fresh state next tick anyway.
+//Frames that fail the checksum, or repeat a sequence number already seen from that sender and
+//stream, are counted and dropped. A sender that's been quiet for SEQUENCE_TIMEOUT starts afresh.
+pub struct RadioMux {
+    pub channel: usize,
+    pub key: u64,
+    pub rejected: u32,
+    received: Option<Frame>,
+    outgoing: [Option<Outgoing>; STREAM_COUNT],
+    sequences: [u8; STREAM_COUNT], //Next sequence number to send, by stream
+    last_sequences: HashMap<(u16, u8), (u8, u32)>, //Last (sequence, tick), by sender and stream
+    next_stream: usize,            //Where flush starts looking, for taking turns
+}
+
+impl Default for RadioMux {
+    fn default() -> Self {
+        Self {
+            channel: DEFAULT_CHANNEL,
+            key: TEAM_KEY,
+            rejected: 0,
+            received: None,
+            outgoing: [None; STREAM_COUNT],
+            sequences: [0; STREAM_COUNT],
+            last_sequences: HashMap::new(),
+            next_stream: 0,
+        }
+    }
+}
+
+impl RadioMux {
+    pub fn new() -> RadioMux {
+        return Default::default();
+    }
+
+    //Call every tick before anything reads or queues messages
+    pub fn tick(&mut self) {
+        set_radio_channel(self.channel);
+        self.received = None;
+
+        let message = match receive() {
+            Some(message) => message,
+            None => return,
+        };
+        let frame = match decode(message, self.key) {
+            Some(frame) => frame,
+            None => {
+                self.rejected += 1;
+                return;
+            }
+        };
+
+        //Replayed or out of date
+        let stream_key = (frame.sender, frame.stream);
+        if let Some(&(last, last_tick)) = self.last_sequences.get(&stream_key) {
+            if !is_fresh(
+                frame.sequence,
+                last,
+                last_tick,
+                current_tick(),
+                SEQUENCE_TIMEOUT,
+            ) {
+                self.rejected += 1;
+                return;
+            }
+        }
+        self.last_sequences
+            .insert(stream_key, (frame.sequence, current_tick()));
+        self.received = Some(frame);
+    }
+
+    //The frame received this tick, if it's on stream
+    pub fn received(&self, stream: u8) -> Option<Frame> {
+        return self.received.filter(|frame| frame.stream == stream);
+    }
+
+    //Replaces anything already queued on stream this tick
+    pub fn queue(&mut self, stream: u8, tag: u8, payload: [f64; 3]) {
+        if let Some(outgoing) = self.outgoing.get_mut(stream as usize) {
+            *outgoing = Some(Outgoing {
+                tag: tag,
+                payload: payload,
+            });
+        }
+    }
+
+    //Call every tick after everything has queued. sender is our Identity ID.
+    pub fn flush(&mut self, sender: u16) {
+        for i in 0..STREAM_COUNT {
+            let stream = (self.next_stream + i) % STREAM_COUNT;
+            let outgoing = match self.outgoing[stream] {
+                Some(outgoing) => outgoing,
+                None => continue,
+            };
+
+            let frame = Frame {
+                tag: outgoing.tag,
+                stream: stream as u8,
+                sender: sender,
+                sequence: self.sequences[stream],
+                payload: outgoing.payload,
+            };
+            send(encode(&frame, self.key));
+            self.sequences[stream] = self.sequences[stream].wrapping_add(1);
+            self.next_stream = stream + 1;
+            break;
+        }
+        self.outgoing = [None; STREAM_COUNT];
+    }
+}
@@ -0,0 +1,50 @@
+use oort_api::prelude::*;
+
+//Small deterministic xorshift generator. Seed from the scenario's seed() so runs are repeatable
+//but different ships and scenarios get different sequences.
+pub struct Rng {
+    state: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        return Rng::new(0);
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        //xorshift gets stuck on zero
+        let state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        return Rng {
+            state: if state == 0 { 1 } else { state },
+        };
+    }
+
+    //Seeded from the game seed mixed with salt, so several generators on one ship differ
+    pub fn from_game_seed(salt: u64) -> Rng {
+        let seed = seed();
+        return Rng::new((seed as u64) ^ ((seed >> 64) as u64) ^ salt);
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    //Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    //Uniform in [min, max)
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        return min + (max - min) * self.next_f64();
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        return self.next_u64() & 1 == 1;
+    }
+}
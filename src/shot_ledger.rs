@@ -0,0 +1,154 @@
+use oort_api::prelude::*;
+use std::collections::VecDeque;
+
+use crate::class_info::class_info;
+use crate::tracking::{Track, TrackTable};
+
+//How quickly the bias follows new misses
+const BIAS_SMOOTHING: f64 = 0.1;
+//Shots are dropped unresolved if the target hasn't been seen this close to their impact tick
+const MAX_TRACK_AGE: u32 = 10;
+
+pub struct Shot {
+    pub target_id: Option<u32>, //TrackTable id of the target, None for the fallback target
+    pub fire_tick: u32,
+    pub impact_tick: u32,    //Tick the bullet was predicted to reach the target
+    pub bullet_origin: Vec2, //World position the bullet left from
+    pub bullet_velocity: Vec2, //World velocity including our own
+
+    //Where we expected the target to be at impact_tick, world
+    pub predicted_target_pos: Vec2,
+}
+
+#[derive(Clone, Copy)]
+pub struct ShotResult {
+    pub hit: bool,
+    pub time_of_flight: f64, //s
+    pub miss_distance: f64,  //m, closest approach between bullet and target centre
+    //Actual target position at impact minus the predicted one. Positive along_track means the
+    //target was further along its velocity than predicted (we were trailing).
+    pub prediction_error: Vec2,
+    pub along_track: f64,
+    pub cross_track: f64,
+}
+
+//Remembers shots until their predicted impact time, then checks them against where the
+//target actually was to classify hit/miss and learn the systematic prediction error.
+#[derive(Default)]
+pub struct ShotLedger {
+    pub pending: VecDeque<Shot>,
+    pub resolved: u32,
+    pub hits: u32,
+    pub dropped: u32, //Shots that couldn't be checked because the target wasn't seen
+    pub total_miss_distance: f64,
+    pub bias: Vec2,            //Smoothed prediction_error, world frame
+    pub along_track_bias: f64, //Smoothed prediction_error along the target's velocity
+    pub cross_track_bias: f64, //Smoothed prediction_error left of the target's velocity
+    pub last_result: Option<ShotResult>,
+}
+
+impl ShotLedger {
+    pub fn new() -> ShotLedger {
+        return Default::default();
+    }
+
+    pub fn record(&mut self, shot: Shot) {
+        self.pending.push_back(shot);
+    }
+
+    //Call every tick after the tracks have been updated. Each shot is checked against the track it
+    //was fired at, so switching targets doesn't turn earlier shots into bogus misses.
+    //Returns the shots resolved this tick.
+    pub fn tick(&mut self, contacts: &TrackTable, fallback: &Track) -> Vec<ShotResult> {
+        let mut results = Vec::new();
+        while let Some(shot) = self.pending.front() {
+            if shot.impact_tick > current_tick() {
+                break;
+            }
+            let shot = self.pending.pop_front().unwrap();
+
+            let target = match shot.target_id {
+                Some(id) => contacts.get(id),
+                None => Some(fallback),
+            };
+            //Target's track was pruned, there's nothing to check the shot against
+            let target = match target {
+                Some(target) => target,
+                None => {
+                    self.dropped += 1;
+                    continue;
+                }
+            };
+            if target.age() > MAX_TRACK_AGE {
+                self.dropped += 1;
+                continue;
+            }
+
+            let result = ShotLedger::resolve(&shot, target);
+            self.resolved += 1;
+            if result.hit {
+                self.hits += 1;
+            }
+            self.total_miss_distance += result.miss_distance;
+            self.bias += (result.prediction_error - self.bias) * BIAS_SMOOTHING;
+            self.along_track_bias += (result.along_track - self.along_track_bias) * BIAS_SMOOTHING;
+            self.cross_track_bias += (result.cross_track - self.cross_track_bias) * BIAS_SMOOTHING;
+            self.last_result = Some(result);
+            results.push(result);
+        }
+        return results;
+    }
+
+    fn resolve(shot: &Shot, target: &Track) -> ShotResult {
+        //Target state extrapolated to now, in case it wasn't observed this exact tick
+        let age = target.age() as f64 * TICK_LENGTH;
+        let target_pos = target.position + target.velocity * age;
+
+        let time_of_flight = (current_tick() - shot.fire_tick) as f64 * TICK_LENGTH;
+        let bullet_pos = shot.bullet_origin + shot.bullet_velocity * time_of_flight;
+
+        //Closest approach assuming both move in straight lines around the impact time
+        let relative_pos = bullet_pos - target_pos;
+        let relative_vel = shot.bullet_velocity - target.velocity;
+        let relative_speed_squared = relative_vel.dot(relative_vel);
+        let mut closest = relative_pos;
+        if relative_speed_squared > 0.0 {
+            closest = relative_pos
+                - relative_vel * (relative_pos.dot(relative_vel) / relative_speed_squared);
+        }
+        let miss_distance = closest.length();
+
+        let prediction_error = target_pos - shot.predicted_target_pos;
+        let mut along_track = 0.0;
+        let mut cross_track = 0.0;
+        if target.velocity.length() > 0.0 {
+            let forward = target.velocity.normalize();
+            let left = vec2(-forward.y, forward.x);
+            along_track = prediction_error.dot(forward);
+            cross_track = prediction_error.dot(left);
+        }
+
+        return ShotResult {
+            hit: miss_distance <= class_info(target.class).radius,
+            time_of_flight: time_of_flight,
+            miss_distance: miss_distance,
+            prediction_error: prediction_error,
+            along_track: along_track,
+            cross_track: cross_track,
+        };
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        if self.resolved == 0 {
+            return 0.0;
+        }
+        return self.hits as f64 / self.resolved as f64;
+    }
+
+    pub fn mean_miss_distance(&self) -> f64 {
+        if self.resolved == 0 {
+            return 0.0;
+        }
+        return self.total_miss_distance / self.resolved as f64;
+    }
+}
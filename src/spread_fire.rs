@@ -0,0 +1,225 @@
+use oort_api::prelude::*;
+
+use crate::class_info::class_info;
+use crate::constants::*;
+use crate::fire_control::{self, BASE_MISS_SIGMA};
+use crate::kinematics::*;
+use crate::maneuver_envelope::*;
+use crate::tracking::Track;
+
+const DEFAULT_DIRECTIONS: usize = 6;
+//Spread mode kicks in when the target's unmodelled acceleration is this fraction of its class max
+const DEFAULT_UNCERTAINTY_THRESHOLD: f64 = 0.25;
+//The unmaneuvered intercept is this many times as likely as each extreme maneuver
+const CENTRE_WEIGHT: f64 = 2.0;
+//Shots at a candidate are forgotten with this half life, so coverage is rebuilt as geometry changes
+const COVERAGE_HALF_LIFE: f64 = 1.0; //s
+
+pub struct Candidate {
+    pub intercept: Vec2, //Relative to us, same frame as predict_intercept
+    pub weight: f64,     //Relative likelihood of the target flying this maneuver
+}
+
+//Aims at a spread of intercepts covering the maneuvers the target could fly during the bullet's
+//time of flight, rather than a single lead point. Candidates are the lead corrected intercept plus
+//the intercepts along the edge of the target's maneuver envelope, shifted by the same correction.
+//Shots go to whichever candidate is least covered relative to its weight.
+pub struct SpreadFire {
+    pub directions: usize,
+    pub uncertainty_threshold: f64,
+    coverage: Vec<f64>, //Decaying shot count per candidate, index 0 is the centre
+}
+
+impl Default for SpreadFire {
+    fn default() -> Self {
+        Self {
+            directions: DEFAULT_DIRECTIONS,
+            uncertainty_threshold: DEFAULT_UNCERTAINTY_THRESHOLD,
+            coverage: vec![0.0; DEFAULT_DIRECTIONS + 1],
+        }
+    }
+}
+
+impl SpreadFire {
+    pub fn new() -> SpreadFire {
+        return Default::default();
+    }
+
+    //Whether the target is maneuvering enough that a single lead point is likely to miss
+    pub fn is_needed(&self, target: &Track) -> bool {
+        let max_accel = class_info(target.class).max_accel();
+        if max_accel <= 0.0 {
+            return false;
+        }
+        return target.accel_error >= max_accel * self.uncertainty_threshold;
+    }
+
+    //target_delta and relative_velocity are relative to us, as for predict_intercept.
+    //intercept is the lead corrected intercept, the maneuvers are offsets from it.
+    pub fn candidates(
+        &self,
+        target_delta: Vec2,
+        relative_velocity: Vec2,
+        intercept: Vec2,
+        target: &Track,
+    ) -> Vec<Candidate> {
+        let info = class_info(target.class);
+        let forward = track_forward(target);
+        let unmaneuvered = predict_intercept(
+            target_delta,
+            relative_velocity,
+            target.accel,
+            target.jerk,
+            BULLET_SPEED,
+        );
+        let correction = intercept - unmaneuvered;
+
+        let mut candidates = Vec::with_capacity(self.directions + 1);
+        candidates.push(Candidate {
+            intercept: intercept,
+            weight: CENTRE_WEIGHT,
+        });
+
+        for i in 0..self.directions {
+            let angle = i as f64 / self.directions as f64 * TAU;
+            let maneuver = max_maneuver_accel(&info, forward, angle);
+
+            candidates.push(Candidate {
+                intercept: predict_intercept(
+                    target_delta,
+                    relative_velocity,
+                    maneuver,
+                    vec2(0.0, 0.0),
+                    BULLET_SPEED,
+                ) + correction,
+                weight: 1.0,
+            });
+        }
+
+        return candidates;
+    }
+
+    //Call every tick
+    pub fn tick(&mut self) {
+        if self.coverage.len() != self.directions + 1 {
+            self.coverage = vec![0.0; self.directions + 1];
+        }
+
+        let decay = f64::powf(0.5, TICK_LENGTH / COVERAGE_HALF_LIFE);
+        for coverage in self.coverage.iter_mut() {
+            *coverage *= decay;
+        }
+    }
+
+    //Index of the candidate to aim the next shot at
+    pub fn select(&self, candidates: &[Candidate]) -> usize {
+        let mut best_index = 0;
+        let mut best_score = f64::MIN;
+        for (i, candidate) in candidates.iter().enumerate() {
+            let coverage = self.coverage.get(i).copied().unwrap_or(0.0);
+            let score = candidate.weight / (1.0 + coverage);
+            if score > best_score {
+                best_score = score;
+                best_index = i;
+            }
+        }
+        return best_index;
+    }
+
+    //Hit probability of a shot fired along gun_heading, if the target flies one of the sampled
+    //maneuvers. Candidates close enough together are covered by the same shot.
+    pub fn hit_probability(&self, candidates: &[Candidate], gun_heading: f64, radius: f64) -> f64 {
+        let total: f64 = candidates.iter().map(|c| c.weight).sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+
+        let mut covered = 0.0;
+        for candidate in candidates {
+            covered += candidate.weight * aim_probability(candidate, gun_heading, radius);
+        }
+        return covered / total;
+    }
+
+    pub fn record_shot(&mut self, index: usize) {
+        if let Some(coverage) = self.coverage.get_mut(index) {
+            *coverage += 1.0;
+        }
+    }
+}
+
+//Hit probability of a shot fired along gun_heading if the target flies this candidate's maneuver.
+//Spread shots are gated on this rather than the coverage, which any one shot only adds to.
+pub fn aim_probability(candidate: &Candidate, gun_heading: f64, radius: f64) -> f64 {
+    let aim_error = angle_diff(gun_heading, candidate.intercept.angle());
+    let miss_distance = aim_error.sin().abs() * candidate.intercept.length();
+    return fire_control::hit_probability(miss_distance, BASE_MISS_SIGMA, radius);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fire_control::FireControl;
+
+    const RADIUS: f64 = 10.0;
+
+    //Centre plus DEFAULT_DIRECTIONS edges, far enough apart that no shot covers two
+    fn separated_candidates() -> Vec<Candidate> {
+        let mut candidates = vec![Candidate {
+            intercept: vec2(1000.0, 0.0),
+            weight: CENTRE_WEIGHT,
+        }];
+        for i in 0..DEFAULT_DIRECTIONS {
+            let angle = (i as f64 - 2.5) * 0.1;
+            candidates.push(Candidate {
+                intercept: vec2(1000.0, 0.0).rotate(angle),
+                weight: 1.0,
+            });
+        }
+        return candidates;
+    }
+
+    #[test]
+    fn edge_candidates_get_fired_on() {
+        let threshold = FireControl::new().threshold;
+        let candidates = separated_candidates();
+        let mut spread_fire = SpreadFire::new();
+        let mut shots = vec![0; candidates.len()];
+
+        //Fire every 5 ticks with the gun laid on whichever candidate is selected
+        for tick in 0..600 {
+            spread_fire.tick();
+            if tick % 5 != 0 {
+                continue;
+            }
+            let index = spread_fire.select(&candidates);
+            let gun_heading = candidates[index].intercept.angle();
+
+            //On its own a separated edge is below the threshold, it's only covered over time
+            assert!(
+                index == 0
+                    || spread_fire.hit_probability(&candidates, gun_heading, RADIUS) < threshold
+            );
+            if aim_probability(&candidates[index], gun_heading, RADIUS) >= threshold {
+                spread_fire.record_shot(index);
+                shots[index] += 1;
+            }
+        }
+
+        for (i, count) in shots.iter().enumerate() {
+            assert!(*count > 0, "candidate {} never fired on", i);
+        }
+        //The centre is twice as likely, so gets more of the shots
+        assert!(shots[0] > shots[1]);
+    }
+
+    #[test]
+    fn aim_probability_falls_off_with_aim_error() {
+        let candidate = Candidate {
+            intercept: vec2(1000.0, 0.0),
+            weight: 1.0,
+        };
+        assert!(aim_probability(&candidate, 0.0, RADIUS) > 0.99);
+        assert!(aim_probability(&candidate, 0.05, RADIUS) < 0.01);
+    }
+}
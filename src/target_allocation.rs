@@ -0,0 +1,98 @@
+use oort_api::prelude::*;
+use std::collections::HashMap;
+
+use crate::identity::Identity;
+use crate::radio::mux::*;
+
+//Message tags on STREAM_ALLOCATION
+const MSG_CLAIM: u8 = 0; //[target x, target y, 0]
+const MSG_IDLE: u8 = 1; //Not engaging anything, [0, 0, 0]
+
+//Claims not refreshed for this many of the claimer's slots are from a dead ship or a dead target
+const CLAIM_TIMEOUT_SLOTS: u32 = 3;
+
+struct Claim {
+    position: Vec2, //World position of the claimed target when the claim was sent
+    tick: u32,
+}
+
+//Spreads a team across enemy targets. Each ship broadcasts the target it's engaging in its own
+//time slot. Claims from higher ranked ships discount those targets in our prioritization, so
+//targets are effectively auctioned off in rank order: the commander takes the best target, the
+//next ship the best of what's left and so on. A dead target drops out of the claimer's contacts
+//and it moves on. A dead ship's claim times out and its target goes back up for auction.
+//Claims double as the heartbeat that keeps us in everyone's Identity.
+#[derive(Default)]
+pub struct TargetAllocation {
+    claims: HashMap<u16, Claim>, //By ID of the claiming ship
+    member_count: usize,
+}
+
+impl TargetAllocation {
+    pub fn new() -> TargetAllocation {
+        return Default::default();
+    }
+
+    //Targets engaged by ships that pick before us, for prioritize
+    pub fn ally_targets(&self, identity: &Identity) -> Vec<Vec2> {
+        let rank = match identity.rank() {
+            Some(rank) => rank,
+            None => return Vec::new(),
+        };
+        return self
+            .claims
+            .iter()
+            .filter(|(id, _)| identity.rank_of(**id).map_or(false, |other| other < rank))
+            .map(|(_, claim)| claim.position)
+            .collect();
+    }
+
+    //Call every tick after Identity with the target we're engaging
+    pub fn tick(&mut self, radio: &mut RadioMux, identity: &mut Identity, target: Option<Vec2>) {
+        self.member_count = identity.member_count().max(1);
+
+        if let Some(frame) = radio.received(STREAM_ALLOCATION) {
+            if identity.is_member(frame.sender) {
+                identity.heard(frame.sender);
+                if frame.tag == MSG_CLAIM {
+                    self.claims.insert(
+                        frame.sender,
+                        Claim {
+                            position: vec2(frame.payload[0], frame.payload[1]),
+                            tick: current_tick(),
+                        },
+                    );
+                } else if frame.tag == MSG_IDLE {
+                    self.claims.remove(&frame.sender);
+                }
+            }
+        }
+
+        let timeout = CLAIM_TIMEOUT_SLOTS * self.frame_length();
+        self.claims
+            .retain(|_, claim| current_tick() - claim.tick <= timeout);
+
+        let rank = match identity.rank() {
+            Some(rank) => rank,
+            None => return,
+        };
+        if !self.is_our_slot(rank) {
+            return;
+        }
+        match target {
+            Some(target) => radio.queue(STREAM_ALLOCATION, MSG_CLAIM, [target.x, target.y, 0.0]),
+            None => radio.queue(STREAM_ALLOCATION, MSG_IDLE, [0.0, 0.0, 0.0]),
+        }
+    }
+
+    //Ticks between one ship's claims. Claims go out on odd ticks, each rank in turn, leaving
+    //even ticks free for formation traffic.
+    fn frame_length(&self) -> u32 {
+        return 2 * self.member_count as u32;
+    }
+
+    fn is_our_slot(&self, rank: usize) -> bool {
+        return current_tick() % 2 == 1
+            && (current_tick() / 2) as usize % self.member_count == rank;
+    }
+}
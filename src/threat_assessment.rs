@@ -0,0 +1,112 @@
+use oort_api::prelude::*;
+
+use crate::constants::*;
+use crate::fire_control::FireControl;
+use crate::kinematics::*;
+use crate::tracking::{Track, TrackTable};
+
+//Time to impact at which the urgency bonus halves
+const URGENCY_TIME: f64 = 5.0; //s
+
+//Contacts within this distance of an ally's target count as engaged by that ally
+const ALLY_TARGET_RADIUS: f64 = 100.0; //m
+
+//Each ally already engaging a contact multiplies its score by this
+const ALLY_ENGAGED_FACTOR: f64 = 0.5;
+//Floor on hit probability so contacts we can't hit yet still rank by threat
+const MIN_HIT_FACTOR: f64 = 0.05;
+
+pub struct TargetScore {
+    pub id: u32,
+    pub score: f64,
+    pub class_weight: f64,
+    pub time_to_impact: f64, //s, infinite if not closing on us
+    pub hit_probability: f64,
+    pub allies_engaged: u32,
+}
+
+//How much we care about killing each class, all else being equal
+pub fn class_weight(class: Class) -> f64 {
+    return match class {
+        Class::Torpedo => 4.0,
+        Class::Missile => 3.0,
+        Class::Fighter => 2.0,
+        Class::Frigate => 1.5,
+        Class::Cruiser => 1.0,
+        Class::Target => 1.0,
+        _ => 0.0,
+    };
+}
+
+//Seconds until a contact reaches us at its current closing speed
+pub fn time_to_impact(track: &Track) -> f64 {
+    let relative_pos = track.current_position() - position();
+    let relative_vel = track.velocity - velocity();
+    let distance = relative_pos.length();
+    if distance <= 0.0 {
+        return 0.0;
+    }
+
+    let closing_speed = -relative_pos.dot(relative_vel) / distance;
+    if closing_speed <= 0.0 {
+        return f64::INFINITY;
+    }
+    return distance / closing_speed;
+}
+
+pub fn score_target(
+    track: &Track,
+    fire_control: &FireControl,
+    ally_targets: &[Vec2],
+) -> TargetScore {
+    let class_weight = class_weight(track.class);
+    let time_to_impact = time_to_impact(track);
+    let urgency = 1.0 + 1.0 / (1.0 + time_to_impact / URGENCY_TIME);
+
+    //Best case hit probability, as if we were already pointing at the intercept
+    let intercept = predict_intercept(
+        track.current_position() - position(),
+        track.velocity - velocity(),
+        track.accel,
+        track.jerk,
+        BULLET_SPEED,
+    );
+    let hit_probability = fire_control.evaluate(0.0, intercept, track).hit_probability;
+
+    let position = track.current_position();
+    let allies_engaged = ally_targets
+        .iter()
+        .filter(|ally_target| (**ally_target - position).length() <= ALLY_TARGET_RADIUS)
+        .count() as u32;
+
+    let score = class_weight
+        * urgency
+        * hit_probability.max(MIN_HIT_FACTOR)
+        * ALLY_ENGAGED_FACTOR.powf(allies_engaged as f64);
+
+    return TargetScore {
+        id: track.id,
+        score: score,
+        class_weight: class_weight,
+        time_to_impact: time_to_impact,
+        hit_probability: hit_probability,
+        allies_engaged: allies_engaged,
+    };
+}
+
+//Every contact worth shooting, best first. ally_targets are world positions of the contacts
+//allies are already engaging.
+pub fn prioritize(
+    contacts: &TrackTable,
+    fire_control: &FireControl,
+    ally_targets: &[Vec2],
+) -> Vec<TargetScore> {
+    let mut scores: Vec<TargetScore> = contacts
+        .tracks
+        .iter()
+        .map(|track| score_target(track, fire_control, ally_targets))
+        .filter(|score| score.score > 0.0)
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    return scores;
+}
@@ -0,0 +1,150 @@
+use oort_api::prelude::*;
+
+use crate::class_info::class_info;
+
+//How quickly accel_error follows new observations. Higher = more responsive, noisier.
+const ACCEL_ERROR_SMOOTHING: f64 = 0.1;
+//Observations further than this (plus how far the contact could have maneuvered) from a track's
+//extrapolated position start a new track
+const ASSOCIATION_GATE: f64 = 50.0; //m
+
+//Kinematic state of a contact (or our own ship) estimated from successive observations.
+//Acceleration and jerk are finite differences of the observed velocity.
+#[derive(Clone, Copy)]
+pub struct Track {
+    pub id: u32, //Unique within a TrackTable, 0 for standalone tracks
+    pub class: Class,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub accel: Vec2,
+    pub jerk: Vec2,
+
+    //Smoothed magnitude of the difference between the acceleration we predicted and the one
+    //observed, m/s^2. This is how much the contact is maneuvering beyond our model.
+    pub accel_error: f64,
+    pub last_update_tick: u32,
+    pub updates: u32,
+}
+
+impl Default for Track {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            class: Class::Unknown,
+            position: vec2(0.0, 0.0),
+            velocity: vec2(0.0, 0.0),
+            accel: vec2(0.0, 0.0),
+            jerk: vec2(0.0, 0.0),
+            accel_error: 0.0,
+            last_update_tick: 0,
+            updates: 0,
+        }
+    }
+}
+
+impl Track {
+    pub fn new(class: Class, position: Vec2, velocity: Vec2) -> Track {
+        return Track {
+            class: class,
+            position: position,
+            velocity: velocity,
+            last_update_tick: current_tick(),
+            updates: 1,
+            ..Default::default()
+        };
+    }
+
+    //Call with a fresh observation. Safe to call with gaps between ticks.
+    pub fn update(&mut self, position: Vec2, velocity: Vec2) {
+        let tick = current_tick();
+        if self.updates > 0 && tick > self.last_update_tick {
+            let dt = (tick - self.last_update_tick) as f64 * TICK_LENGTH;
+            let accel = (velocity - self.velocity) / dt;
+
+            if self.updates > 1 {
+                let predicted_accel = self.accel + self.jerk * dt;
+                let error = (accel - predicted_accel).length();
+                self.accel_error += (error - self.accel_error) * ACCEL_ERROR_SMOOTHING;
+                self.jerk = (accel - self.accel) / dt;
+            }
+
+            self.accel = accel;
+        }
+
+        self.position = position;
+        self.velocity = velocity;
+        self.last_update_tick = tick;
+        self.updates += 1;
+    }
+
+    //Ticks since the last observation
+    pub fn age(&self) -> u32 {
+        return current_tick().saturating_sub(self.last_update_tick);
+    }
+
+    //Position seconds after the last observation, assuming acceleration holds
+    pub fn extrapolate(&self, seconds: f64) -> Vec2 {
+        return self.position + self.velocity * seconds + self.accel * (0.5 * seconds * seconds);
+    }
+
+    //Best guess of where the contact is now
+    pub fn current_position(&self) -> Vec2 {
+        return self.extrapolate(self.age() as f64 * TICK_LENGTH);
+    }
+}
+
+//Tracks of every contact seen on radar. Observations are associated to the nearest track of the
+//same class whose extrapolated position is close enough, otherwise they start a new track.
+#[derive(Default)]
+pub struct TrackTable {
+    pub tracks: Vec<Track>,
+    next_id: u32,
+}
+
+impl TrackTable {
+    pub fn new() -> TrackTable {
+        return Default::default();
+    }
+
+    //Returns the id of the track updated or created
+    pub fn observe(&mut self, class: Class, position: Vec2, velocity: Vec2) -> u32 {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, track) in self.tracks.iter().enumerate() {
+            if track.class != class {
+                continue;
+            }
+
+            let age = track.age() as f64 * TICK_LENGTH;
+            let gate = ASSOCIATION_GATE + 0.5 * class_info(class).max_accel() * age * age;
+            let distance = (track.extrapolate(age) - position).length();
+            if distance > gate {
+                continue;
+            }
+
+            match best {
+                Some((_, best_distance)) if best_distance <= distance => {}
+                _ => best = Some((i, distance)),
+            }
+        }
+
+        if let Some((i, _)) = best {
+            self.tracks[i].update(position, velocity);
+            return self.tracks[i].id;
+        }
+
+        self.next_id += 1;
+        let mut track = Track::new(class, position, velocity);
+        track.id = self.next_id;
+        self.tracks.push(track);
+        return self.next_id;
+    }
+
+    //Forget tracks not observed for more than max_age ticks
+    pub fn prune(&mut self, max_age: u32) {
+        self.tracks.retain(|track| track.age() <= max_age);
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Track> {
+        return self.tracks.iter().find(|track| track.id == id);
+    }
+}
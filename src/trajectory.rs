@@ -0,0 +1,89 @@
+use oort_api::prelude::*;
+
+use crate::debug_draw::{self, DebugCategory};
+use crate::kinematics::*;
+use crate::tracking::Track;
+
+//Spread (m) at which a predicted point is considered a coin flip
+const CONFIDENCE_HALF_DISTANCE: f64 = 50.0;
+
+pub struct TrajectoryPoint {
+    pub position: Vec2,
+    pub ticks: i32,      //Ticks from now
+    pub confidence: f64, //1 = certain, tends to 0 as the possible spread grows
+}
+
+//Future positions every step ticks up to ticks ahead, assuming accel and jerk stay as they are.
+//accel_uncertainty (m/s^2) is how far the real acceleration may stray from accel, and drives confidence.
+pub fn predict_trajectory(
+    position: Vec2,
+    velocity: Vec2,
+    accel: Vec2,
+    jerk: Vec2,
+    accel_uncertainty: f64,
+    ticks: i32,
+    step: i32,
+) -> Vec<TrajectoryPoint> {
+    let step = step.max(1);
+    let mut points = Vec::with_capacity((ticks / step + 1) as usize);
+    points.push(TrajectoryPoint {
+        position: position,
+        ticks: 0,
+        confidence: 1.0,
+    });
+
+    let mut tick = step;
+    while tick <= ticks {
+        let offset = vec2(
+            delta_distance_iterative(tick, velocity.x, accel.x, jerk.x),
+            delta_distance_iterative(tick, velocity.y, accel.y, jerk.y),
+        );
+
+        let time = tick as f64 * TICK_LENGTH;
+        let spread = 0.5 * accel_uncertainty * time * time;
+
+        points.push(TrajectoryPoint {
+            position: position + offset,
+            ticks: tick,
+            confidence: 1.0 / (1.0 + spread / CONFIDENCE_HALF_DISTANCE),
+        });
+        tick += step;
+    }
+
+    return points;
+}
+
+pub fn predict_track_trajectory(track: &Track, ticks: i32, step: i32) -> Vec<TrajectoryPoint> {
+    return predict_trajectory(
+        track.position,
+        track.velocity,
+        track.accel,
+        track.jerk,
+        track.accel_error,
+        ticks,
+        step,
+    );
+}
+
+//Green when confident, fading through yellow to red
+pub fn confidence_color(confidence: f64) -> u32 {
+    let confidence = confidence.clamp(0.0, 1.0);
+    let red = ((1.0 - confidence) * 2.0).min(1.0) * 255.0;
+    let green = (confidence * 2.0).min(1.0) * 255.0;
+    return ((red as u32) << 16) | ((green as u32) << 8);
+}
+
+pub fn draw_trajectory(points: &[TrajectoryPoint]) {
+    if !debug_draw::is_category_enabled(DebugCategory::Prediction) {
+        return;
+    }
+
+    for pair in points.windows(2) {
+        debug_draw::line(
+            DebugCategory::Prediction,
+            pair[0].position,
+            pair[1].position,
+            confidence_color(pair[1].confidence),
+        );
+    }
+}